@@ -0,0 +1,160 @@
+//! Alternative functors: applicatives with choice and failure.
+
+use crate::{
+    ApplicativeOnce, Coherent, FunctorOnce, Mapped, MonadOnce, TypeMap};
+use crate::bifunctor::Proj0;
+
+use std::collections::{LinkedList, VecDeque};
+
+
+pub trait Alternative<T, Tag = ()> : ApplicativeOnce<T, Tag> {
+    /// The failure, and the unit of `alt`.
+    fn empty() -> Self;
+
+    /// Choice between `self` and `other`.
+    fn alt(self, other: Self) -> Self;
+
+    /// Whether this is a failure, i.e., like `empty()`.
+    fn is_empty(&self) -> bool;
+
+    /// Turn failure into `None`, and success into `Some`.
+    fn optional(self) -> Self::Functor<Option<T>>
+        where Self: Sized,
+              Self::Functor<Option<T>>: Alternative<Option<T>, Tag>
+    {
+        self.fmap_once(Some).alt(ApplicativeOnce::pure_once(None))
+    }
+}
+
+/// Monads with choice and failure.
+pub trait MonadPlus<T, Tag = ()> : MonadOnce<T, Tag> + Alternative<T, Tag> {
+    /// Keep only the values satisfying `p`.
+    fn mfilter(self, mut p: impl FnMut(&T) -> bool) -> Self
+        where Self: Sized + TypeMap<T, Tag, Functor<T> = Self>
+    {
+        self.bind_once::<T>(
+            |x| if p(&x) { Self::pure_once(x) } else { Self::empty() })
+    }
+}
+
+impl<T, Tag, F> MonadPlus<T, Tag> for F
+    where F: MonadOnce<T, Tag> + Alternative<T, Tag> {}
+
+
+/// `pure(())` if `c` is true, otherwise `empty()`.
+pub fn guard<Tag, F: Alternative<(), Tag>>(c: bool) -> F {
+    if c { F::pure_once(()) } else { F::empty() }
+}
+
+/// Run `f` repeatedly until it fails, collecting the results.
+///
+/// Rust is strict, so unlike Haskell this only makes sense if `f` has side
+/// effects, e.g., reading from an iterator.  It does not terminate if `f`
+/// never fails.
+pub fn many<T: Clone, Tag, F, G>(mut f: impl FnMut() -> F) -> G
+    where F: Alternative<T, Tag>
+             + FunctorOnce<T, Tag, Functor<Vec<T>> = G>,
+          G: Alternative<Vec<T>, Tag, Functor<T> = F> + Coherent<Vec<T>, Tag>
+{
+    many_from(G::pure_once(Vec::new()), &mut f)
+}
+
+/// Like `many`, but fails if the first call of `f` fails.
+pub fn some<T: Clone, Tag, F, G>(mut f: impl FnMut() -> F) -> G
+    where F: Alternative<T, Tag>
+             + FunctorOnce<T, Tag, Functor<Vec<T>> = G>,
+          G: Alternative<Vec<T>, Tag, Functor<T> = F> + Coherent<Vec<T>, Tag>
+{
+    let x = f();
+    if x.is_empty() {
+        return G::empty();
+    }
+    many_from(x.fmap_once(|x| vec![x]), &mut f)
+}
+
+fn many_from<T: Clone, Tag, F, G>(mut acc: G, f: &mut impl FnMut() -> F) -> G
+    where F: Alternative<T, Tag>,
+          G: Alternative<Vec<T>, Tag, Functor<T> = F> + Coherent<Vec<T>, Tag>
+{
+    loop {
+        let x = f();
+        if x.is_empty() {
+            return acc;
+        }
+        acc = G::lift2_once(|mut v: Vec<T>, x| { v.push(x); v }, acc, x);
+    }
+}
+
+
+/// Option picks the first success.
+impl<T> Alternative<T> for Option<T> {
+    fn empty() -> Option<T> { None }
+    fn alt(self, other: Option<T>) -> Option<T> { self.or(other) }
+    fn is_empty(&self) -> bool { self.is_none() }
+}
+
+/// Result picks the first success, keeping the last error if both fail.
+impl<T, E: Default> Alternative<T, Proj0<E>> for Result<T, E> {
+    fn empty() -> Result<T, E> { Err(E::default()) }
+    fn alt(self, other: Result<T, E>) -> Result<T, E> { self.or(other) }
+    fn is_empty(&self) -> bool { self.is_err() }
+}
+
+/// Collections concatenate.
+impl<T> Alternative<T, Mapped> for Vec<T> {
+    fn empty() -> Vec<T> { Vec::new() }
+    fn alt(mut self, mut other: Vec<T>) -> Vec<T> {
+        self.append(&mut other);
+        self
+    }
+    fn is_empty(&self) -> bool { Vec::is_empty(self) }
+}
+
+impl<T> Alternative<T, Mapped> for LinkedList<T> {
+    fn empty() -> LinkedList<T> { LinkedList::new() }
+    fn alt(mut self, mut other: LinkedList<T>) -> LinkedList<T> {
+        self.append(&mut other);
+        self
+    }
+    fn is_empty(&self) -> bool { LinkedList::is_empty(self) }
+}
+
+impl<T> Alternative<T, Mapped> for VecDeque<T> {
+    fn empty() -> VecDeque<T> { VecDeque::new() }
+    fn alt(mut self, mut other: VecDeque<T>) -> VecDeque<T> {
+        self.append(&mut other);
+        self
+    }
+    fn is_empty(&self) -> bool { VecDeque::is_empty(self) }
+}
+
+
+#[test]
+fn alt_test() {
+    assert_eq!(None.alt(Some(1)).alt(Some(2)), Some(1));
+    assert_eq!(Option::<u8>::empty(), None);
+    let e: Result<u8, String> = Err("a".into());
+    assert_eq!(e.clone().alt(Err("b".into())), Err("b".into()));
+    assert_eq!(e.alt(Ok(3)), Ok(3));
+    assert_eq!(vec![1, 2].alt(vec![3]), [1, 2, 3]);
+
+    assert_eq!(Some(4).optional(), Some(Some(4)));
+    assert_eq!(None::<u8>.optional(), Some(None));
+    assert_eq!(guard::<_, Option<()>>(false), None);
+    assert_eq!(guard::<_, Vec<()>>(true), [()]);
+    assert_eq!(Some(3).mfilter(|x| x % 2 == 0), None);
+    assert_eq!(vec![1, 2, 3, 4].mfilter(|x| x % 2 == 0), [2, 4]);
+}
+
+#[test]
+fn many_some() {
+    let mut it = [1, 2, 3].into_iter();
+    assert_eq!(many(|| it.next()), Some(vec![1, 2, 3]));
+    assert_eq!(many(|| it.next()), Some(vec![]));
+    assert_eq!(some(|| it.next()), None::<Vec<i32>>);
+
+    let mut digits = "12x".chars();
+    let r: Result<Vec<u32>, ()>
+        = some(|| digits.next().and_then(|c| c.to_digit(10)).ok_or(()));
+    assert_eq!(r, Ok(vec![1, 2]));
+}
@@ -3,26 +3,190 @@
 //! The traits actually work just derived from TypeMap rather than Functor.
 //! But fmap can always be defined in terms of pure and apply!
 
-use crate::{Functor, FunctorOnce, FunctorMut};
+use crate::{Comp1, Functor, FunctorOnce, FunctorMut};
+use crate::bifunctor::Proj0;
 
+use std::ops::Add;
 
-pub trait ApplicativeOnce<T, Tag=()> : FunctorOnce<T, Tag, Item=T> {
-    fn pure_once(x:T) -> Self;
 
+/// Generate `liftN_once`, as nested `lift2_once` calls building up a flat
+/// tuple.  Each stage needs the functor at that tuple type to be applicative,
+/// with the same family of results.
+///
+/// The local names are passed along as `[f a p]`, so that every stage sees the
+/// same identifiers.
+macro_rules! lift_once_n {
+    ($(#[$m:meta])* $name:ident($($u:ident $b:ident $x:ident),+)) => {
+        lift_once_n!(@first [f a p] [$(#[$m])* $name [$($u $b $x),+]]
+                     x0 $($u $b $x),+);
+    };
+    (@first [$f:ident $a:ident $p:ident] $hdr:tt $x0:ident
+     $u:ident $b:ident $x:ident, $($rest:tt)+) => {
+        lift_once_n!(
+            @stage [$f $a $p] $hdr [T $x0, $u $x]
+            [$u: Clone,]
+            [let $p = Self::lift2_once(|$x0, $x| ($x0, $x), $a, $b);]
+            $($rest)+);
+    };
+    (@stage [$f:ident $a:ident $p:ident] $hdr:tt [$($t:ident $tx:ident),+]
+     [$($bd:tt)*] [$($st:tt)*] $u:ident $b:ident $x:ident, $($rest:tt)+) => {
+        lift_once_n!(@stage [$f $a $p] $hdr [$($t $tx,)+ $u $x]
+            [$($bd)* $u: Clone,
+             Self::Functor<($($t),+)>: Apply<($($t),+), Tag,
+                 Functor<$u> = Self::Functor<$u>,
+                 Functor<($($t,)+ $u)> = Self::Functor<($($t,)+ $u)>>,]
+            [$($st)* let $p = <Self::Functor<($($t),+)>>::lift2_once(
+                |($($tx),+), $x| ($($tx,)+ $x), $p, $b);]
+            $($rest)+);
+    };
+    (@stage [$f:ident $a:ident $p:ident]
+     [$(#[$m:meta])* $name:ident [$($u:ident $b:ident $xx:ident),+]]
+     [$($t:ident $tx:ident),+] [$($bd:tt)*] [$($st:tt)*]
+     $ul:ident $bl:ident $xl:ident) => {
+        $(#[$m])*
+        #[allow(clippy::too_many_arguments)]
+        fn $name<$($u,)+ R>($f: impl Fn(T, $($u),+) -> R,
+                            $a: Self, $($b: Self::Functor<$u>),+)
+                            -> Self::Functor<R>
+            where Self: Sized, T: Clone, $($bd)* $ul: Clone,
+                  Self::Functor<($($t),+)>: Apply<($($t),+), Tag,
+                      Functor<$ul> = Self::Functor<$ul>,
+                      Functor<R> = Self::Functor<R>>,
+        {
+            $($st)*
+            <Self::Functor<($($t),+)>>::lift2_once(
+                |($($tx),+), $xl| $f($($tx,)+ $xl), $p, $bl)
+        }
+    };
+}
+
+/// Combining two functors, without `pure`.
+///
+/// This is `ApplicativeOnce` less `pure_once`, so also covers functors like
+/// maps, which can be zipped, but have no lawful `pure`, as that would need a
+/// value for every key.
+pub trait Apply<T, Tag=()> : FunctorOnce<T, Tag, Item=T> {
+    /// (T×U → V) × F(T) × F(U) → F(V)
     fn lift2_once<U, V>(f: impl Fn(T, U) -> V,
                         a: Self, b: Self::Functor<U>) -> Self::Functor<V>
-        where T: Clone, Self::Functor<U>: Clone;
+        where T: Clone, U: Clone;
 
+    /// F(A→U) × F(A) → F(U)
     fn call_once<A, U>(self, x: Self::Functor<A>) -> Self::Functor<U>
-        where T: Fn(A) -> U, Self::Functor<A> : Clone;
+        where T: Fn(A) -> U, A: Clone;
 
-    fn apply_once<U, F: Fn(T) -> U>(self, f: Self::Functor<F>)
-                                    -> Self::Functor<U>
-        where T: Clone, Self: Sized, Self::Functor<F>: Clone
+    fn apply_once<U, F: Fn(T) -> U + Clone>(self, f: Self::Functor<F>)
+                                            -> Self::Functor<U>
+        where T: Clone, Self: Sized
     { Self::lift2_once(|x,f| f(x), self, f) }
+
+    /// `lift2_once` as a method, combining `self` and `b` with `f`.
+    fn zip_with<U, V>(self, b: Self::Functor<U>, f: impl Fn(T, U) -> V)
+                      -> Self::Functor<V>
+        where T: Clone, Self: Sized, U: Clone
+    { Self::lift2_once(f, self, b) }
+
+    /// Pair up the values of `self` and `b`.
+    fn product<U>(self, b: Self::Functor<U>) -> Self::Functor<(T, U)>
+        where T: Clone, Self: Sized, U: Clone
+    { Self::lift2_once(|x, y| (x, y), self, b) }
+
+    lift_once_n!(
+        /// `lift2_once` with three arguments, combined left to right.
+        ///
+        /// The higher arities are the same.  The [`lift!`](crate::lift) macro
+        /// does the same nesting at the call site, for any number of
+        /// arguments, and also by reference, through `Applicative::lift2`.
+        lift3_once(U1 b1 x1, U2 b2 x2));
+    lift_once_n!(lift4_once(U1 b1 x1, U2 b2 x2, U3 b3 x3));
+    lift_once_n!(lift5_once(U1 b1 x1, U2 b2 x2, U3 b3 x3, U4 b4 x4));
+    lift_once_n!(
+        lift6_once(U1 b1 x1, U2 b2 x2, U3 b3 x3, U4 b4 x4, U5 b5 x5));
+    lift_once_n!(lift7_once(
+        U1 b1 x1, U2 b2 x2, U3 b3 x3, U4 b4 x4, U5 b5 x5, U6 b6 x6));
+    lift_once_n!(lift8_once(U1 b1 x1, U2 b2 x2, U3 b3 x3, U4 b4 x4,
+                            U5 b5 x5, U6 b6 x6, U7 b7 x7));
+}
+
+pub trait ApplicativeOnce<T, Tag=()> : Apply<T, Tag> {
+    fn pure_once(x:T) -> Self;
+}
+
+
+/// Run the effect `f` only if `cond` holds, otherwise `pure_once(())`.
+pub fn when<F: ApplicativeOnce<(), Tag>, Tag>(cond: bool, f: impl FnOnce() -> F)
+                                              -> F {
+    if cond { f() } else { F::pure_once(()) }
+}
+
+/// Run the effect `f` only if `cond` does not hold.
+pub fn unless<F: ApplicativeOnce<(), Tag>, Tag>(cond: bool,
+                                                f: impl FnOnce() -> F) -> F {
+    when(!cond, f)
 }
 
 
+/// Lift a function of any number of arguments into an applicative.
+///
+/// `lift!(f, a, b, c)` combines the values `a`, `b`, `c` with nested
+/// `Apply::lift2_once`, so, as for that, the elements must be `Clone`.
+/// `lift!(f, &a, &b, &c)` uses `Applicative::lift2` by reference, which needs
+/// no `Clone`, e.g., for the cartesian product of `Vec`s.
+///
+/// ```
+/// # use functor::lift;
+/// assert_eq!(lift!(|x, y, z| x + y * z, Some(1), Some(2), Some(3)), Some(7));
+/// let (a, b) = (vec![1, 2], vec!['a']);
+/// let v = lift!(|x: &u8, y: &char| format!("{x}{y}"), &a, &b);
+/// assert_eq!(v, ["1a", "2a"]);
+/// ```
+#[macro_export]
+macro_rules! lift {
+    ($f:expr, $(& $a:expr),+ $(,)?) => {
+        $crate::lift!(@ref $f; []; $($a),+)
+    };
+    ($f:expr, $($a:expr),+ $(,)?) => {
+        $crate::lift!(@val $f; []; $($a),+)
+    };
+    // Pair each argument with a fresh identifier.
+    (@$m:ident $f:expr; [$($x:ident $e:expr),*]; $a:expr $(, $as:expr)*) => {
+        $crate::lift!(@$m $f; [$($x $e,)* x $a]; $($as),*)
+    };
+    (@val $f:expr; [$x:ident $e:expr $(, $xs:ident $es:expr)+];) => {
+        $crate::Apply::lift2_once(
+            |$x, $crate::lift!(@pat $($xs),+)| $f($x $(, $xs)+),
+            $e, $crate::lift!(@nest $($es),+))
+    };
+    (@ref $f:expr; [$x:ident $e:expr $(, $xs:ident $es:expr)+];) => {
+        $crate::Applicative::lift2(
+            |$x, $crate::lift!(@rpat $($xs),+)| $f($x $(, $xs)+),
+            &$e, $crate::lift!(@rnest $($es),+))
+    };
+    (@pat $x:ident) => { $x };
+    (@pat $x:ident $(, $xs:ident)+) => { ($x, $crate::lift!(@pat $($xs),+)) };
+    (@nest $e:expr) => { $e };
+    (@nest $e:expr $(, $es:expr)+) => {
+        $crate::Apply::lift2_once(
+            |x, y| (x, y), $e, $crate::lift!(@nest $($es),+))
+    };
+    (@rpat $x:ident) => { $x };
+    (@rpat $x:ident $(, $xs:ident)+) => {
+        &($x, $crate::lift!(@rpat $($xs),+))
+    };
+    (@rnest $e:expr) => { &$e };
+    (@rnest $e:expr $(, $es:expr)+) => {
+        &$crate::Applicative::lift2(
+            |x, y| (x, y), &$e, $crate::lift!(@rnest $($es),+))
+    };
+}
+
+
+/// Applicative by reference, so the elements need not be `Clone`.
+///
+/// There is no `lift3` and up, unlike `lift3_once`: nesting `lift2` needs the
+/// intermediate functor of pairs borrowed for `'a`, which a method cannot
+/// give.  `lift!(f, &a, &b, &c)` does the nesting at the call site instead,
+/// where the intermediate is a temporary.
 pub trait Applicative<'a, T: 'a, Tag=()> : Functor<'a, T, Tag> {
     /// The T → F(T) morphism.
     ///
@@ -54,9 +218,7 @@ pub trait ApplicativeMut<'a, T, Tag=()> : FunctorMut<'a, T, Tag, Item=T> {
                     -> Self::Functor<U>;
 }
 
-impl<T> ApplicativeOnce<T> for Option<T> {
-    fn pure_once(x: T) -> Option<T> { Some(x) }
-
+impl<T> Apply<T> for Option<T> {
     fn lift2_once<U, V>(f: impl Fn(T, U) -> V,
                         a: Option<T>, b: Option<U>) -> Option<V> {
         Some(f(a?, b?))
@@ -71,6 +233,44 @@ impl<T> ApplicativeOnce<T> for Option<T> {
     }
 }
 
+impl<T> ApplicativeOnce<T> for Option<T> {
+    fn pure_once(x: T) -> Option<T> { Some(x) }
+}
+
+/// Result is applicative on the `Ok` value, short-circuiting on the first
+/// error.
+impl<T, E> Apply<T, Proj0<E>> for Result<T, E> {
+    fn lift2_once<U, V>(f: impl Fn(T, U) -> V,
+                        a: Result<T, E>, b: Result<U, E>) -> Result<V, E> {
+        Ok(f(a?, b?))
+    }
+
+    fn call_once<A, B>(self, x: Result<A, E>) -> Result<B, E>
+        where T: Fn(A) -> B {
+        Ok(self?(x?))
+    }
+}
+
+impl<T, E> ApplicativeOnce<T, Proj0<E>> for Result<T, E> {
+    fn pure_once(x: T) -> Result<T, E> { Ok(x) }
+}
+
+/// Pairs are applicative on `.1`, accumulating `.0` with `+`, and with
+/// `Default` as the unit.
+impl<A: Add<Output = A>, T> Apply<T, Comp1> for (A, T) {
+    fn lift2_once<U, V>(f: impl Fn(T, U) -> V, a: (A, T), b: (A, U)) -> (A, V) {
+        (a.0 + b.0, f(a.1, b.1))
+    }
+
+    fn call_once<B, U>(self, x: (A, B)) -> (A, U) where T: Fn(B) -> U {
+        (self.0 + x.0, (self.1)(x.1))
+    }
+}
+
+impl<A: Default + Add<Output = A>, T> ApplicativeOnce<T, Comp1> for (A, T) {
+    fn pure_once(x: T) -> (A, T) { (A::default(), x) }
+}
+
 impl<'a, T: 'a> Applicative<'a, T> for Option<T> {
     fn pure(x : &T) -> Option<T> where T: Clone { Some(x.clone()) }
 
@@ -89,6 +289,28 @@ impl<'a, T: Clone> ApplicativeMut<'a, T> for Option<T> {
     }
 }
 
+/// Arrays are applicative pointwise: `pure` replicates, and each function is
+/// applied to the element in the same position.
+impl<'a, T: Clone, const N: usize> ApplicativeMut<'a, T> for [T; N] {
+    fn mut_pure(x : &T) -> [T; N] { std::array::from_fn(|_| x.clone()) }
+    fn mut_apply<U>(&mut self, f : &mut [impl FnMut(&mut T) -> U; N])
+                    -> [U; N] {
+        std::array::from_fn(|i| f[i](&mut self[i]))
+    }
+}
+
+/// Pairs are applicative on `.1`, accumulating `.0` with `+`, and with
+/// `Default` as the unit.
+impl<'a, A: Copy + Default + Add<Output = A>, T: Clone>
+    ApplicativeMut<'a, T, Comp1> for (A, T)
+{
+    fn mut_pure(x : &T) -> (A, T) { (A::default(), x.clone()) }
+    fn mut_apply<U>(&mut self, f : &mut (A, impl FnMut(&mut T) -> U))
+                    -> (A, U) {
+        (self.0 + f.0, f.1(&mut self.1))
+    }
+}
+
 #[test]
 fn apply_option() {
     assert_eq!(None.apply_once(Some(|x:u32| x)), None);
@@ -96,3 +318,62 @@ fn apply_option() {
     assert_eq!(Some(1).apply_once(n), None);
     assert_eq!(Some(3).apply_once(Some(|x| x*x)), Some(9));
 }
+
+#[test]
+fn apply_mut() {
+    let mut a = [1, 2, 3];
+    let mut f = [|x: &mut i32| { *x += 1; *x }; 3];
+    assert_eq!(a.mut_apply(&mut f), [2, 3, 4]);
+    assert_eq!(a, [2, 3, 4]);
+    assert_eq!(<[u8; 2]>::mut_pure(&7), [7, 7]);
+
+    let mut p = (1u32, String::from("a"));
+    let mut f = (2u32, |s: &mut String| { s.push('b'); s.len() });
+    assert_eq!(p.mut_apply(&mut f), (3, 2));
+    assert_eq!(p.1, "ab");
+    assert_eq!(<(u32, char) as ApplicativeMut<_, Comp1>>::mut_pure(&'x'),
+               (0, 'x'));
+}
+
+#[test]
+fn lift_n() {
+    let r = Option::lift3_once(|x, y: u8, z: &str| format!("{x}{y}{z}"),
+                               Some(1), Some(2), Some("c"));
+    assert_eq!(r.as_deref(), Some("12c"));
+    let r = Option::lift8_once(|a, b, c, d, e, f, g, h| a + b + c + d + e + f
+                                   + g + h,
+                               Some(1), Some(2), Some(3), Some(4), Some(5),
+                               Some(6), Some(7), Some(8));
+    assert_eq!(r, Some(36));
+    let e = Result::lift4_once(|a: u8, b: u8, c: u8, d: u8| a * b * c * d,
+                               Ok(1), Ok(2), Err("three"), Ok(4));
+    assert_eq!(e, Err("three"));
+
+    let v = Vec::lift3_once(|x, y, z| x + y + z, vec![0, 10], vec![100],
+                            vec![1, 2]);
+    assert_eq!(v, [101, 102, 111, 112]);
+    assert_eq!(lift!(|x, y, z| x + y + z, vec![0, 10], vec![100], vec![1, 2]),
+               v);
+
+    // By reference, the elements need not be `Clone`.
+    struct NoClone(u32);
+    let (a, b) = (vec![NoClone(1), NoClone(2)], vec![NoClone(10)]);
+    let c = vec![NoClone(100), NoClone(200)];
+    let w = lift!(|x: &NoClone, y: &NoClone, z: &NoClone| x.0 + y.0 + z.0,
+                  &a, &b, &c);
+    assert_eq!(w, [111, 211, 112, 212]);
+}
+
+#[test]
+fn zip_when() {
+    assert_eq!(vec![1, 2].product(vec!['a']), [(1, 'a'), (2, 'a')]);
+    assert_eq!(Apply::zip_with(Some(2), Some(3), |x, y| x * y),
+               Some(6));
+    assert_eq!(Ok::<u8, ()>(1).product(Err::<char, _>(())), Err(()));
+
+    let check = |x: i32| unless(x >= 0, || Err(format!("{x} < 0")));
+    assert_eq!(check(1), Ok(()));
+    assert_eq!(check(-1), Err("-1 < 0".into()));
+    assert_eq!(when(false, || None), Some(()));
+    assert_eq!(when(true, || vec![(), ()]), [(), ()]);
+}
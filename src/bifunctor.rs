@@ -1,7 +1,9 @@
 
-use crate::{Functor, FunctorOnce, TypeMap};
+use crate::{
+    ApplicativeOnce, Coherent, Functor, FunctorMut, FunctorOnce, TypeMap};
 
 use std::marker::PhantomData;
+use std::ops::Add;
 
 /// Base trait for BiFunctor.  This has the mapping on types, but no
 /// functionality.
@@ -35,9 +37,14 @@ pub trait BiFunctor<A, B, Tag = ()> : BiTypeMap<A, B, Tag>
 
 
 /// Trait for a BiFunctor that works on mutable references.
+///
+/// The maps are not instances.  Their keys cannot be handed out as `&mut`, and
+/// `f` may give new keys of any type, which could be neither hashed nor
+/// ordered to build the result.  `FunctorWithKey::fmap_with_key_mut` is the
+/// map version, with each key read-only and its value mutable.
 pub trait BiFunctorMut<A, B, Tag = ()> : BiTypeMap<A, B, Tag>
 {
-    fn fmap_mut2<T, U>(&self,
+    fn fmap_mut2<T, U>(&mut self,
                        f: impl FnMut(&mut A) -> T, g: impl FnMut(&mut B) -> U)
                        -> Self::BiFunctor<T, U>;
 }
@@ -63,6 +70,40 @@ impl<A, B> BiFunctor<A, B> for (A, B) {
     }
 }
 
+impl<A, B> BiFunctorMut<A, B> for (A, B) {
+    fn fmap_mut2<T,U>(&mut self, mut f: impl FnMut(&mut A)->T,
+                      mut g: impl FnMut(&mut B)->U) -> (T, U) {
+        (f(&mut self.0), g(&mut self.1))
+    }
+}
+
+
+/// Result is a bifunctor, with only one of the maps used.
+impl<A, B> BiTypeMap<A, B> for Result<A, B> {
+    type BiFunctor<T, U> = Result<T, U>;
+}
+
+impl<A, B> BiFunctorOnce<A, B> for Result<A, B> {
+    fn fmap_once2<T,U>(self, f: impl FnMut(A)->T, g: impl FnMut(B)->U)
+                       -> Result<T, U> {
+        self.map(f).map_err(g)
+    }
+}
+
+impl<A, B> BiFunctor<A, B> for Result<A, B> {
+    fn fmap2<T,U>(&self, f: impl FnMut(&A)->T, g: impl FnMut(&B)->U)
+                  -> Result<T, U> {
+        self.as_ref().map(f).map_err(g)
+    }
+}
+
+impl<A, B> BiFunctorMut<A, B> for Result<A, B> {
+    fn fmap_mut2<T,U>(&mut self, f: impl FnMut(&mut A)->T,
+                      g: impl FnMut(&mut B)->U) -> Result<T, U> {
+        self.as_mut().map(f).map_err(g)
+    }
+}
+
 
 pub struct Proj0<B>(PhantomData<B>);
 pub struct Proj1<A>(PhantomData<A>);
@@ -91,6 +132,17 @@ impl<'a, A: 'a, B: Clone, C: BiFunctor<A, B>> Functor<'a, A, Proj0<B>> for C
 }
 
 
+/// A bifunctor can be specialized to a functor on it's first argument.  Note
+/// that this implementation clones the preserved data.
+impl<'a, A, B: Clone, C: BiFunctorMut<A, B>> FunctorMut<'a, A, Proj0<B>> for C
+{
+    fn fmap_mut<T>(&mut self, f: impl FnMut(&mut A) -> T)
+                   -> <Self as BiTypeMap<A, B>>::BiFunctor<T, B> {
+        self.fmap_mut2(f, |y| y.clone())
+    }
+}
+
+
 /// A bifunctor can be specialized to a functor on it's second argument.
 impl<A, B, C: BiTypeMap<A, B>> TypeMap<B, Proj1<A>> for C {
     type Functor<T> = <Self as BiTypeMap<A, B>>::BiFunctor<A, T>;
@@ -114,15 +166,163 @@ impl<'a, A: Clone, B: 'a, C: BiFunctor<A, B>> Functor<'a, B, Proj1<A>> for C
     }
 }
 
+/// A bifunctor can be specialized to a functor on it's second argument.  Note
+/// that this implementation clones the preserved data.
+impl<'a, A: Clone, B, C: BiFunctorMut<A, B>> FunctorMut<'a, B, Proj1<A>> for C
+{
+    fn fmap_mut<T>(&mut self, g: impl FnMut(&mut B) -> T)
+                   -> <Self as BiTypeMap<A, B>>::BiFunctor<A, T> {
+        self.fmap_mut2(|x| x.clone(), g)
+    }
+}
+
 pub trait BiCoherent<A, B, Tag = ()> : BiTypeMap<A, B, Tag> {
     fn cohere<S, T, U, V>(
         x : <Self::BiFunctor<S, T> as BiTypeMap<S, T, Tag>>::BiFunctor<U, V>)
         -> Self::BiFunctor<U, V>;
 }
 
+/// The `BiCoherent` analogue of `coherent!`: `cohere` is the identity, with the
+/// result given at the method's type parameters `U` and `V`, e.g.,
+///
+/// `bicoherent!(impl[A, B] BiCoherent<A, B> for (A, B) => (U, V));`
+#[macro_export]
+macro_rules! bicoherent {
+    (impl[$($g:tt)*] BiCoherent<$a:ty, $b:ty $(, $tag:ty)?> for $s:ty
+     => $v:ty) => {
+        impl<$($g)*> $crate::bifunctor::BiCoherent<$a, $b $(, $tag)?> for $s {
+            fn cohere<S, T, U, V>(x: $v) -> $v { x }
+        }
+    };
+}
 
-impl<A, B> BiCoherent<A, B> for (A, B) {
-    fn cohere<S, T, U, V>(p : (U, V)) -> (U, V) { p }
+bicoherent!(impl[A, B] BiCoherent<A, B> for (A, B) => (U, V));
+bicoherent!(impl[A, B] BiCoherent<A, B> for Result<A, B> => Result<U, V>);
+
+/// A coherent bifunctor is coherent on its first argument.
+impl<A, B, C> Coherent<A, Proj0<B>> for C
+    where C: BiCoherent<A, B> + BiTypeMap<A, B, BiFunctor<A, B> = C>
+{
+    fn cohere<U, V>(
+        x: <C::BiFunctor<U, B> as BiTypeMap<U, B>>::BiFunctor<V, B>)
+        -> C::BiFunctor<V, B> {
+        <C as BiCoherent<A, B>>::cohere::<U, B, V, B>(x)
+    }
+}
+
+/// A coherent bifunctor is coherent on its second argument.
+impl<A, B, C> Coherent<B, Proj1<A>> for C
+    where C: BiCoherent<A, B> + BiTypeMap<A, B, BiFunctor<A, B> = C>
+{
+    fn cohere<U, V>(
+        x: <C::BiFunctor<A, U> as BiTypeMap<A, U>>::BiFunctor<A, V>)
+        -> C::BiFunctor<A, V> {
+        <C as BiCoherent<A, B>>::cohere::<A, U, A, V>(x)
+    }
+}
+
+
+/// Folding both sides of a bifunctor.  As for the writer applicative on pairs,
+/// the results are combined with `+`, and `Default` is the unit.
+pub trait Bifoldable<A, B, Tag = ()> {
+    fn bifold_map<M>(self, f: impl FnMut(A) -> M, g: impl FnMut(B) -> M) -> M
+        where M: Default + Add<Output = M>;
+}
+
+/// Traversing both sides of a bifunctor with an applicative, e.g., turning
+/// `(Option<A>, Option<B>)` into `Option<(A, B)>`.
+///
+/// `L` is the result type on the first side.  This is a parameter of the trait,
+/// like the new key type of `ReKey`, so that maps can require it to be `Hash`
+/// or `Ord`.  The results are combined one at a time with `lift2_once`, so, as
+/// for that, they must be `Clone`.
+pub trait Bitraversable<A, B, L, Tag = ()> : Bifoldable<A, B, Tag> {
+    /// The result of traversing, at `U` on the second side.
+    type Bitraversed<U>;
+
+    fn bitraverse<U, ATag, F>(self, f: impl FnMut(A) -> F,
+                              g: impl FnMut(B) -> F::Functor<U>)
+                              -> F::Functor<Self::Bitraversed<U>>
+        where L: Clone, U: Clone,
+              F: ApplicativeOnce<L, ATag> + Coherent<L, ATag>,
+              F::Functor<U>: FunctorOnce<U, ATag, Item = U>,
+              Self::Bitraversed<U>: Clone,
+              F::Functor<Self::Bitraversed<U>>:
+                  ApplicativeOnce<Self::Bitraversed<U>, ATag,
+                                  Functor<(L, U)> = F::Functor<(L, U)>>
+                  + Coherent<Self::Bitraversed<U>, ATag>;
+
+    /// Both sides are already applicatives, so pull them out.
+    fn bisequence<U, ATag>(self) -> A::Functor<Self::Bitraversed<U>>
+        where Self: Sized,
+              L: Clone, U: Clone,
+              A: ApplicativeOnce<L, ATag, Functor<U> = B> + Coherent<L, ATag>,
+              B: FunctorOnce<U, ATag, Item = U>,
+              Self::Bitraversed<U>: Clone,
+              A::Functor<Self::Bitraversed<U>>:
+                  ApplicativeOnce<Self::Bitraversed<U>, ATag,
+                                  Functor<(L, U)> = A::Functor<(L, U)>>
+                  + Coherent<Self::Bitraversed<U>, ATag>
+    {
+        self.bitraverse::<U, ATag, A>(|a| a, |b| b)
+    }
+}
+
+
+impl<A, B> Bifoldable<A, B> for (A, B) {
+    fn bifold_map<M>(self, mut f: impl FnMut(A) -> M,
+                     mut g: impl FnMut(B) -> M) -> M
+        where M: Default + Add<Output = M> {
+        f(self.0) + g(self.1)
+    }
+}
+
+impl<A, B, L> Bitraversable<A, B, L> for (A, B) {
+    type Bitraversed<U> = (L, U);
+
+    fn bitraverse<U, ATag, F>(self, mut f: impl FnMut(A) -> F,
+                              mut g: impl FnMut(B) -> F::Functor<U>)
+                              -> F::Functor<(L, U)>
+        where L: Clone, U: Clone,
+              F: ApplicativeOnce<L, ATag> + Coherent<L, ATag>,
+              F::Functor<U>: FunctorOnce<U, ATag, Item = U>,
+              (L, U): Clone,
+              F::Functor<(L, U)>:
+                  ApplicativeOnce<(L, U), ATag,
+                                  Functor<(L, U)> = F::Functor<(L, U)>>
+                  + Coherent<(L, U), ATag>
+    {
+        F::lift2_once(|l, u| (l, u), f(self.0), g(self.1))
+    }
+}
+
+impl<A, B> Bifoldable<A, B> for Result<A, B> {
+    fn bifold_map<M>(self, f: impl FnMut(A) -> M, g: impl FnMut(B) -> M) -> M
+        where M: Default + Add<Output = M> {
+        self.map_or_else(g, f)
+    }
+}
+
+impl<A, B, L> Bitraversable<A, B, L> for Result<A, B> {
+    type Bitraversed<U> = Result<L, U>;
+
+    fn bitraverse<U, ATag, F>(self, mut f: impl FnMut(A) -> F,
+                              mut g: impl FnMut(B) -> F::Functor<U>)
+                              -> F::Functor<Result<L, U>>
+        where L: Clone, U: Clone,
+              F: ApplicativeOnce<L, ATag> + Coherent<L, ATag>,
+              F::Functor<U>: FunctorOnce<U, ATag, Item = U>,
+              Result<L, U>: Clone,
+              F::Functor<Result<L, U>>:
+                  ApplicativeOnce<Result<L, U>, ATag,
+                                  Functor<(L, U)> = F::Functor<(L, U)>>
+                  + Coherent<Result<L, U>, ATag>
+    {
+        match self {
+            Ok(a) => f(a).fmap_once(Ok),
+            Err(b) => F::cohere::<U, Result<L, U>>(g(b).fmap_once(Err)),
+        }
+    }
 }
 
 
@@ -139,4 +339,41 @@ fn bif() {
     assert_eq!(q, ("4".into(), "5".into()));
 }
 
+#[test]
+fn bif_mut() {
+    use super::{BiFunctorMut, Proj0, Proj1};
+    use crate::FunctorMut;
+
+    let mut p: (u8, String) = (4, "a".into());
+    let q = p.fmap_mut2(|x| { *x += 1; *x * 2 }, |s| { s.push('b'); s.len() });
+    assert_eq!(p, (5, "ab".into()));
+    assert_eq!(q, (10, 2));
+
+    let mut r: Result<u32, String> = Err("e".into());
+    let s = FunctorMut::<_, Proj1<u32>>::fmap_mut(&mut r, |e| { e.push('!'); 0 });
+    assert_eq!(r, Err("e!".into()));
+    assert_eq!(s, Err(0));
+    let mut r: Result<u32, String> = Ok(2);
+    let s = FunctorMut::<_, Proj0<String>>::fmap_mut(&mut r, |x| { *x *= 3; 1 });
+    assert_eq!((r, s), (Ok(6), Ok(1)));
+}
+
+#[test]
+fn bitraverse_test() {
+    use super::{Bifoldable, Bitraversable};
+
+    assert_eq!((Some(1), Some('a')).bisequence(), Some((1, 'a')));
+    assert_eq!((Some(1), None::<char>).bisequence(), None);
+    let r: Result<Option<u8>, Option<&str>> = Err(Some("e"));
+    assert_eq!(r.bisequence(), Some(Err("e")));
+
+    let parse = |s: &str| s.parse::<u8>().map_err(|_| s.to_string());
+    let p: Result<(u8, u8), String> = ("1", "2").bitraverse(parse, parse);
+    assert_eq!(p, Ok((1, 2)));
+    assert_eq!(("1", "x").bitraverse(parse, parse), Err("x".into()));
+
+    assert_eq!((2, "abc").bifold_map(|x| x, str::len), 5);
+    assert_eq!(Err::<u8, &str>("ab").bifold_map(|_| 0, str::len), 2);
+}
+
 }
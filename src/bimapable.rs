@@ -3,11 +3,14 @@
 //! is pairing.
 
 use crate::{
-    BiCoherent, BiFunctorOnce, BiTypeMap, Functor, FunctorOnce, TypeMap};
+    ApplicativeOnce, Apply, BiCoherent, BiFunctorOnce, BiTypeMap, Coherent,
+    Functor, FunctorMut, FunctorMutate, FunctorOnce, TypeMap};
+use crate::bifunctor::{Bifoldable, Bitraversable};
 
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::hash::{BuildHasher, Hash};
 use std::marker::PhantomData;
+use std::ops::Add;
 
 
 pub trait BiMapable<K, T, Tag=()>
@@ -16,9 +19,14 @@ pub trait BiMapable<K, T, Tag=()>
 
     /// Note that we only need to change type on the second coordinate!
     type Collection<U> :
-    FromIterator<<Self::Member as BiTypeMap<K, T, Tag>>::BiFunctor<K, U>> +
+    Extend<<Self::Member as BiTypeMap<K, T, Tag>>::BiFunctor<K, U>> +
+    IntoIterator<Item=<Self::Member as BiTypeMap<K, T, Tag>>::BiFunctor<K, U>> +
     BiMapable<K, U, Tag,
               Member=<Self::Member as BiTypeMap<K, T, Tag>>::BiFunctor<K, U>>;
+
+    /// An empty collection to map `self` into.  This is how a `HashMap` keeps
+    /// its hasher, without needing `S: Default` to make a fresh one.
+    fn empty_like<U>(&self) -> Self::Collection<U>;
 }
 
 pub struct Derived<K, Tag>(PhantomData<K>, PhantomData<Tag>);
@@ -32,7 +40,9 @@ impl<K, T, Tag, C: BiMapable<K, T, Tag>> FunctorOnce<T, Derived<K, Tag>> for C
     where C: IntoIterator<Item=C::Member>
 {
     fn fmap_once<U>(self, mut f: impl FnMut(T) -> U) -> Self::Functor<U> {
-        self.into_iter().map(|x| x.fmap_once2(|k| k, &mut f)).collect()
+        let mut out = self.empty_like();
+        out.extend(self.into_iter().map(|x| x.fmap_once2(|k| k, &mut f)));
+        out
     }
 }
 
@@ -45,29 +55,331 @@ impl<'a, K: 'a + Clone, T: 'a, Tag, C: 'a + BiMapable<K, T, Tag>>
              : BiFunctorOnce<&'a K, &'a T, Tag>,
 {
     fn fmap<U>(&'a self, mut f: impl FnMut(&T) -> U) -> C::Collection<U> {
-        self.into_iter()
-            .map(|v| v.fmap_once2(|k| k.clone(), &mut f))
-            .map(C::Member::cohere::<&'a K, &'a T, K, U>)
-            .collect()
+        let mut out = self.empty_like();
+        out.extend(self.into_iter()
+                   .map(|v| v.fmap_once2(|k| k.clone(), &mut f))
+                   .map(C::Member::cohere::<&'a K, &'a T, K, U>));
+        out
     }
 }
 
+/// Mutating the values of a BiMapable, with the keys cloned.  Maps are not a
+/// `BiFunctorMut`, see there, so `fmap_with_key_mut` is the way to see the keys.
+impl<'a, K: 'a + Clone, T: 'a, Tag, C: 'a + BiMapable<K, T, Tag>>
+    FunctorMut<'a, T, Derived<K, Tag>> for C
+    where
+    &'a mut C: IntoIterator<
+        Item = <C::Member as BiTypeMap<K, T, Tag>>::BiFunctor<&'a K, &'a mut T>>,
+    <C::Member as BiTypeMap<K, T, Tag>>::BiFunctor<&'a K, &'a mut T>
+             : BiFunctorOnce<&'a K, &'a mut T, Tag>,
+{
+    fn fmap_mut<U>(&'a mut self, mut f: impl FnMut(&mut T) -> U)
+                   -> C::Collection<U> {
+        let mut out = self.empty_like();
+        out.extend(self.into_iter()
+                   .map(|v| v.fmap_once2(|k| k.clone(), &mut f))
+                   .map(C::Member::cohere::<&'a K, &'a mut T, K, U>));
+        out
+    }
+}
 
-/// HashMap becomes a mapable with no change on the key type.
+/// HashMap becomes a mapable with no change on the key type.  Mapping keeps
+/// the hasher and the capacity, but rehashes the keys, unless done in place.
 ///
 /// Unfortunately we can't turn HashSet into a Functor, because it only works on
 /// a subset of types.
-impl<K: Eq+Hash, T, S: BuildHasher + Default>
+impl<K: Eq+Hash, T, S: BuildHasher + Clone>
     BiMapable<K, T> for HashMap<K, T, S>
 {
     type Member = (K, T);
     type Collection<U> = HashMap<K, U, S>;
+
+    fn empty_like<U>(&self) -> HashMap<K, U, S> {
+        HashMap::with_capacity_and_hasher(self.capacity(),
+                                          self.hasher().clone())
+    }
 }
 
 impl<K: Ord, T> BiMapable<K, T> for BTreeMap<K, T>
 {
     type Member = (K, T);
     type Collection<U> = BTreeMap<K, U>;
+
+    fn empty_like<U>(&self) -> BTreeMap<K, U> { BTreeMap::new() }
+}
+
+crate::coherent!(
+    impl[K: Eq + Hash, T, S: BuildHasher + Clone]
+    Coherent<T, Derived<K, ()>> for HashMap<K, T, S> => HashMap<K, V, S>);
+crate::coherent!(impl[K: Ord, T] Coherent<T, Derived<K, ()>>
+                 for BTreeMap<K, T> => BTreeMap<K, V>);
+
+/// Mapping the values in place.
+impl<K: Eq + Hash, T, S: BuildHasher + Clone> FunctorMutate<T, Derived<K, ()>>
+    for HashMap<K, T, S>
+{
+    fn fmutate(&mut self, f: impl FnMut(&mut T)) {
+        self.values_mut().for_each(f)
+    }
+}
+
+impl<K: Ord, T> FunctorMutate<T, Derived<K, ()>> for BTreeMap<K, T> {
+    fn fmutate(&mut self, f: impl FnMut(&mut T)) {
+        self.values_mut().for_each(f)
+    }
+}
+
+/// Extend `out` with `f` of the values of `a` and their matches, removed
+/// from the other map by `remove`.
+fn inner_join<K, T, U, V, R: Extend<(K, V)>>(
+    mut out: R, a: impl IntoIterator<Item = (K, T)>,
+    mut remove: impl FnMut(&K) -> Option<U>, mut f: impl FnMut(T, U) -> V)
+    -> R
+{
+    out.extend(a.into_iter().filter_map(
+        |(k, x)| { let y = remove(&k)?; Some((k, f(x, y))) }));
+    out
+}
+
+/// Maps combine on the keys present in both, an inner join.
+impl<K: Eq + Hash, T, S: BuildHasher + Clone> Apply<T, Derived<K, ()>>
+    for HashMap<K, T, S>
+{
+    fn lift2_once<U, V>(f: impl Fn(T, U) -> V, a: HashMap<K, T, S>,
+                        mut b: HashMap<K, U, S>) -> HashMap<K, V, S> {
+        inner_join(a.empty_like(), a, |k| b.remove(k), f)
+    }
+    fn call_once<A, U>(self, mut x: HashMap<K, A, S>) -> HashMap<K, U, S>
+        where T: Fn(A) -> U
+    {
+        inner_join(self.empty_like(), self, |k| x.remove(k), |g, y| g(y))
+    }
+}
+
+/// Maps combine on the keys present in both, an inner join.
+impl<K: Ord, T> Apply<T, Derived<K, ()>> for BTreeMap<K, T> {
+    fn lift2_once<U, V>(f: impl Fn(T, U) -> V, a: BTreeMap<K, T>,
+                        mut b: BTreeMap<K, U>) -> BTreeMap<K, V> {
+        inner_join(BTreeMap::new(), a, |k| b.remove(k), f)
+    }
+    fn call_once<A, U>(self, mut x: BTreeMap<K, A>) -> BTreeMap<K, U>
+        where T: Fn(A) -> U
+    {
+        inner_join(BTreeMap::new(), self, |k| x.remove(k), |g, y| g(y))
+    }
+}
+
+
+/// Maps fold over each key and then its value, in iteration order.
+impl<K: Eq + Hash, T, S: BuildHasher> Bifoldable<K, T>
+    for HashMap<K, T, S>
+{
+    fn bifold_map<M>(self, mut f: impl FnMut(K) -> M,
+                     mut g: impl FnMut(T) -> M) -> M
+        where M: Default + Add<Output = M> {
+        self.into_iter().fold(M::default(), |m, (k, v)| m + f(k) + g(v))
+    }
+}
+
+impl<K: Ord, T> Bifoldable<K, T> for BTreeMap<K, T> {
+    fn bifold_map<M>(self, mut f: impl FnMut(K) -> M,
+                     mut g: impl FnMut(T) -> M) -> M
+        where M: Default + Add<Output = M> {
+        self.into_iter().fold(M::default(), |m, (k, v)| m + f(k) + g(v))
+    }
+}
+
+/// Traversing the keys as well as the values.  New keys that collide keep the
+/// last value, as for `collect`; use `ReKey` for more control.
+impl<K: Eq + Hash, T, L: Eq + Hash, S: BuildHasher + Clone>
+    Bitraversable<K, T, L> for HashMap<K, T, S>
+{
+    type Bitraversed<U> = HashMap<L, U, S>;
+
+    fn bitraverse<U, ATag, F>(self, mut f: impl FnMut(K) -> F,
+                              mut g: impl FnMut(T) -> F::Functor<U>)
+                              -> F::Functor<HashMap<L, U, S>>
+        where L: Clone, U: Clone,
+              F: ApplicativeOnce<L, ATag> + Coherent<L, ATag>,
+              F::Functor<U>: FunctorOnce<U, ATag, Item = U>,
+              HashMap<L, U, S>: Clone,
+              F::Functor<HashMap<L, U, S>>:
+                  ApplicativeOnce<HashMap<L, U, S>, ATag,
+                                  Functor<(L, U)> = F::Functor<(L, U)>>
+                  + Coherent<HashMap<L, U, S>, ATag>
+    {
+        let map = HashMap::with_capacity_and_hasher(
+            self.len(), self.hasher().clone());
+        let mut acc = <F::Functor<HashMap<L, U, S>>>::pure_once(map);
+        for (k, v) in self {
+            let e = F::lift2_once(|l, u| (l, u), f(k), g(v));
+            acc = <F::Functor<HashMap<L, U, S>>>::lift2_once(
+                |mut m, (l, u)| { m.insert(l, u); m }, acc, e);
+        }
+        acc
+    }
+}
+
+/// Traversing the keys as well as the values, in key order.  New keys that
+/// collide keep the last value, as for `collect`; use `ReKey` for more control.
+impl<K: Ord, T, L: Ord> Bitraversable<K, T, L> for BTreeMap<K, T> {
+    type Bitraversed<U> = BTreeMap<L, U>;
+
+    fn bitraverse<U, ATag, F>(self, mut f: impl FnMut(K) -> F,
+                              mut g: impl FnMut(T) -> F::Functor<U>)
+                              -> F::Functor<BTreeMap<L, U>>
+        where L: Clone, U: Clone,
+              F: ApplicativeOnce<L, ATag> + Coherent<L, ATag>,
+              F::Functor<U>: FunctorOnce<U, ATag, Item = U>,
+              BTreeMap<L, U>: Clone,
+              F::Functor<BTreeMap<L, U>>:
+                  ApplicativeOnce<BTreeMap<L, U>, ATag,
+                                  Functor<(L, U)> = F::Functor<(L, U)>>
+                  + Coherent<BTreeMap<L, U>, ATag>
+    {
+        let mut acc = <F::Functor<BTreeMap<L, U>>>::pure_once(BTreeMap::new());
+        for (k, v) in self {
+            let e = F::lift2_once(|l, u| (l, u), f(k), g(v));
+            acc = <F::Functor<BTreeMap<L, U>>>::lift2_once(
+                |mut m, (l, u)| { m.insert(l, u); m }, acc, e);
+        }
+        acc
+    }
+}
+
+
+/// How to resolve two entries landing on the same key when re-keying a map.
+pub trait CollisionPolicy<V> {
+    /// The result of re-keying to the map `M` with keys `K`.
+    type Output<M, K>;
+
+    /// Combine the value `old` already present with a `new` one for the same
+    /// key.  `None` records the key as a clash.
+    fn collide(&mut self, old: V, new: V) -> Option<V>;
+
+    /// Produce the result from the re-keyed map and the clashing keys.
+    fn finish<M, K>(map: M, clashes: Vec<K>) -> Self::Output<M, K>;
+}
+
+/// Keep the value seen first, in the iteration order of the source map.
+pub struct KeepFirst;
+/// Keep the value seen last, in the iteration order of the source map.
+pub struct KeepLast;
+/// Combine the values with a closure `f(old, new)`.
+pub struct Merge<F>(pub F);
+/// Fail with a [`KeyCollision`] listing every clashing key.
+pub struct Reject;
+
+/// The error from re-keying with [`Reject`].  Each clashing key is listed
+/// once, in order for a `BTreeMap`, and in no particular order otherwise.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyCollision<K>(pub Vec<K>);
+
+impl<K: std::fmt::Debug> std::fmt::Display for KeyCollision<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "colliding keys: {:?}", self.0)
+    }
+}
+
+impl<K: std::fmt::Debug> std::error::Error for KeyCollision<K> {}
+
+impl<V> CollisionPolicy<V> for KeepFirst {
+    type Output<M, K> = M;
+    fn collide(&mut self, old: V, _: V) -> Option<V> { Some(old) }
+    fn finish<M, K>(map: M, _: Vec<K>) -> M { map }
+}
+
+impl<V> CollisionPolicy<V> for KeepLast {
+    type Output<M, K> = M;
+    fn collide(&mut self, _: V, new: V) -> Option<V> { Some(new) }
+    fn finish<M, K>(map: M, _: Vec<K>) -> M { map }
+}
+
+impl<V, F: FnMut(V, V) -> V> CollisionPolicy<V> for Merge<F> {
+    type Output<M, K> = M;
+    fn collide(&mut self, old: V, new: V) -> Option<V> { Some(self.0(old, new)) }
+    fn finish<M, K>(map: M, _: Vec<K>) -> M { map }
+}
+
+impl<V> CollisionPolicy<V> for Reject {
+    type Output<M, K> = Result<M, KeyCollision<K>>;
+    fn collide(&mut self, _: V, _: V) -> Option<V> { None }
+    fn finish<M, K>(map: M, clashes: Vec<K>) -> Result<M, KeyCollision<K>> {
+        if clashes.is_empty() { Ok(map) } else { Err(KeyCollision(clashes)) }
+    }
+}
+
+/// Maps that can change their key type to `L`.
+///
+/// This is separate from BiMapable, because the new keys need `Hash` or `Ord`,
+/// and may collide.
+pub trait ReKey<K, T, L> : Sized {
+    /// The map with keys `L` and values `U`.
+    type ReKeyed<U>;
+
+    /// Map both the keys and values, resolving key collisions with `policy`.
+    fn bimap<U, P: CollisionPolicy<U>>(
+        self, f: impl FnMut(K) -> L, g: impl FnMut(T) -> U, policy: P)
+        -> P::Output<Self::ReKeyed<U>, L>;
+
+    /// Map the keys, resolving key collisions with `policy`.
+    fn map_keys<P: CollisionPolicy<T>>(self, f: impl FnMut(K) -> L, policy: P)
+                                       -> P::Output<Self::ReKeyed<T>, L> {
+        self.bimap(f, |x| x, policy)
+    }
+}
+
+/// The guts of re-keying, given the removal and insertion for the map `M`, and
+/// a set `C` of the clashing keys.
+fn rekey<L, U, M, C, P: CollisionPolicy<U>>(
+    entries: impl Iterator<Item = (L, U)>, mut map: M, mut clashes: C,
+    remove: impl Fn(&mut M, &L) -> Option<U>, insert: impl Fn(&mut M, L, U),
+    mut policy: P) -> P::Output<M, L>
+    where C: Extend<L> + IntoIterator<Item = L>
+{
+    for (l, u) in entries {
+        match remove(&mut map, &l) {
+            None => insert(&mut map, l, u),
+            Some(old) => match policy.collide(old, u) {
+                Some(u) => insert(&mut map, l, u),
+                None => clashes.extend(Some(l)),
+            }
+        }
+    }
+    P::finish(map, clashes.into_iter().collect())
+}
+
+/// The result keeps the hasher of `self`.
+impl<K, T, L: Eq + Hash, S: BuildHasher + Clone> ReKey<K, T, L>
+    for HashMap<K, T, S>
+{
+    type ReKeyed<U> = HashMap<L, U, S>;
+
+    fn bimap<U, P: CollisionPolicy<U>>(
+        self, mut f: impl FnMut(K) -> L, mut g: impl FnMut(T) -> U, policy: P)
+        -> P::Output<HashMap<L, U, S>, L>
+    {
+        let map = HashMap::with_capacity_and_hasher(
+            self.len(), self.hasher().clone());
+        let clashes = HashSet::with_hasher(self.hasher().clone());
+        rekey(self.into_iter().map(|(k, v)| (f(k), g(v))), map, clashes,
+              |m: &mut HashMap<L, U, S>, l| m.remove(l),
+              |m, l, u| { m.insert(l, u); }, policy)
+    }
+}
+
+impl<K, T, L: Ord> ReKey<K, T, L> for BTreeMap<K, T> {
+    type ReKeyed<U> = BTreeMap<L, U>;
+
+    fn bimap<U, P: CollisionPolicy<U>>(
+        self, mut f: impl FnMut(K) -> L, mut g: impl FnMut(T) -> U, policy: P)
+        -> P::Output<BTreeMap<L, U>, L>
+    {
+        rekey(self.into_iter().map(|(k, v)| (f(k), g(v))), BTreeMap::new(),
+              BTreeSet::new(),
+              |m: &mut BTreeMap<L, U>, l| m.remove(l),
+              |m, l, u| { m.insert(l, u); }, policy)
+    }
 }
 
 
@@ -84,6 +396,15 @@ fn hash_map_test() {
     assert_eq!(mapped, expect);
 }
 
+#[test]
+fn map_mut_test() {
+    let mut bm : BTreeMap<u32, String>
+        = [(1, "One".into()), (2, "Two".into())].into_iter().collect();
+    let lengths = bm.fmap_mut(|x| { x.push('!'); x.len() });
+    assert_eq!(lengths, [(1, 4), (2, 4)].into_iter().collect());
+    assert_eq!(bm[&2], "Two!");
+}
+
 #[test]
 fn btree_test() {
     let bm : BTreeMap<u32, &str>
@@ -92,3 +413,117 @@ fn btree_test() {
     let expect = [(1, false), (2, true), (3, false)].into_iter().collect();
     assert_eq!(mapped, expect);
 }
+
+#[test]
+fn rekey_test() {
+    let bm : BTreeMap<u32, &str>
+        = [(1, "One"), (2, "Two"), (3, "Three"), (4, "Four")]
+        .into_iter().collect();
+    let parity = |k: u32| k % 2;
+
+    let first = bm.clone().map_keys(parity, KeepFirst);
+    assert_eq!(first, [(0, "Two"), (1, "One")].into_iter().collect());
+    let last = bm.clone().map_keys(parity, KeepLast);
+    assert_eq!(last, [(0, "Four"), (1, "Three")].into_iter().collect());
+    let merged = bm.clone().bimap(parity, |v| v.len(), Merge(|a, b| a + b));
+    assert_eq!(merged, [(0, 7), (1, 8)].into_iter().collect());
+
+    assert_eq!(bm.clone().map_keys(parity, Reject), Err(KeyCollision(vec![0, 1])));
+    let ok = bm.map_keys(|k| k * 10, Reject).unwrap();
+    assert_eq!(ok.keys().copied().collect::<Vec<_>>(), [10, 20, 30, 40]);
+
+    let hm : HashMap<&str, u32> = [("a", 1), ("A", 2)].into_iter().collect();
+    let err = hm.map_keys(|k| k.to_lowercase(), Reject).unwrap_err();
+    assert_eq!(err, KeyCollision(vec!["a".to_string()]));
+
+    let many: BTreeMap<u32, u32> = (0..1000).map(|k| (k, k)).collect();
+    assert_eq!(many.map_keys(|k| k % 3, Reject),
+               Err(KeyCollision(vec![0, 1, 2])));
+}
+
+#[test]
+fn cohere_test() {
+    let bm : BTreeMap<u32, u32> = [(1, 10), (2, 200)].into_iter().collect();
+    let s = FunctorOnce::<_, Derived<u32, ()>>::fmap_once(bm, |x| x.to_string());
+    let l = s.fmap_once(|x| x.len());
+    let l = <BTreeMap<u32, u32> as Coherent<u32, Derived<u32, ()>>>
+        ::cohere::<String, usize>(l);
+    assert_eq!(l, [(1, 2), (2, 3)].into_iter().collect());
+}
+
+#[test]
+fn apply_test() {
+    fn ratio<F: Apply<f64, Tag>, Tag>(a: F, b: F::Functor<f64>)
+                                      -> F::Functor<f64> {
+        F::lift2_once(|x, y| x / y, a, b)
+    }
+    let hits = BTreeMap::from([("a", 3.0), ("b", 1.0), ("c", 4.0)]);
+    let total = BTreeMap::from([("a", 4.0), ("c", 8.0), ("d", 1.0)]);
+    assert_eq!(ratio(hits, total), BTreeMap::from([("a", 0.75), ("c", 0.5)]));
+    assert_eq!(ratio(Some(1.0), Some(4.0)), Some(0.25));
+    assert_eq!(ratio(vec![1.0, 2.0], vec![2.0]), [0.5, 1.0]);
+
+    let fs: HashMap<u8, fn(u8) -> u8> = HashMap::from([
+        (1, (|x| x + 1) as fn(u8) -> u8), (2, |x| x * 2)]);
+    let r = fs.call_once(HashMap::from([(2, 5), (3, 7)]));
+    assert_eq!(r, HashMap::from([(2, 10)]));
+}
+
+#[test]
+fn bitraverse_map() {
+    let m = BTreeMap::from([(" a", "1"), ("b ", "2")]);
+    fn key(k: &str) -> Result<&str, String> {
+        if k.trim().is_empty() { Err("empty key".into()) } else { Ok(k.trim()) }
+    }
+    let val = |v: &str| v.parse::<u32>().map_err(|e| e.to_string());
+    assert_eq!(m.clone().bitraverse(key, val),
+               Ok(BTreeMap::from([("a", 1), ("b", 2)])));
+    let bad = BTreeMap::from([(" ", "1"), ("c", "x")]);
+    assert_eq!(bad.bitraverse(key, val), Err("empty key".into()));
+    assert_eq!(m.bifold_map(str::len, str::len), 6);
+
+    let h = HashMap::from([(Some(1), Some('a')), (Some(2), Some('b'))]);
+    assert_eq!(h.bisequence(), Some(HashMap::from([(1, 'a'), (2, 'b')])));
+    let h = HashMap::from([(Some(1), Some('a')), (None, Some('b'))]);
+    assert_eq!(h.bisequence::<char, ()>(), None);
+}
+
+#[test]
+fn hasher_kept() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::Hasher;
+
+    /// A seeded hasher, with no `Default`.
+    #[derive(Clone)]
+    struct Seeded(u64);
+    impl BuildHasher for Seeded {
+        type Hasher = DefaultHasher;
+        fn build_hasher(&self) -> DefaultHasher {
+            let mut h = DefaultHasher::new();
+            h.write_u64(self.0);
+            h
+        }
+    }
+
+    let mut m = HashMap::with_capacity_and_hasher(100, Seeded(7));
+    m.extend([(1, "a"), (2, "bb")]);
+    let n = m.fmap(|s| s.len());
+    assert_eq!((n.hasher().0, n.capacity()), (7, m.capacity()));
+    assert_eq!(n[&2], 2);
+    let o = n.fmap_once(|x| x * 10);
+    assert_eq!((o.hasher().0, o.capacity(), o[&1]), (7, m.capacity(), 10));
+    let r = o.map_keys(|_| 0, Merge(|a, b| a + b));
+    assert_eq!((r.hasher().0, r[&0]), (7, 30));
+}
+
+#[test]
+fn maps_mutated() {
+    let mut m = HashMap::with_capacity(100);
+    m.extend([(1, "a"), (2, "bb")]);
+    let cap = m.capacity();
+    m.fmutate(|s| *s = "z");
+    assert_eq!((m.capacity(), m[&1], m[&2]), (cap, "z", "z"));
+    let mut b = BTreeMap::from([(1, 2)]);
+    b.fmutate(|x| *x += 1);
+    assert_eq!(b[&1], 3);
+}
@@ -1,13 +1,15 @@
 
-use crate::{FunctorOnce, Functor, TypeMap};
-use std::{boxed::Box, ops::Deref, rc::Rc};
+use crate::{
+    Applicative, ApplicativeOnce, Apply, Comonad, FunctorMutate, FunctorOnce,
+    Functor, TypeMap};
+use std::{boxed::Box, ops::Deref, rc::Rc, sync::Arc};
 
-trait Boxed<T> : From<T> + Deref<Target=T> {
+pub trait Boxed<T> : From<T> + Deref<Target=T> {
     type Boxed<U> : Boxed<U>;
 }
 
 /// Disambiguating tag.
-struct BoxedTag;
+pub struct BoxedTag;
 
 impl<B, T> TypeMap<T, BoxedTag> for B where B : Boxed<T> {
     type Functor<U> = B::Boxed<U>;
@@ -19,15 +21,120 @@ impl<'a, T: 'a, B> Functor<'a, T, BoxedTag> for B where B : Boxed<T> {
     }
 }
 
+/// Smart pointers are trivially comonads.
+impl<T, B> Comonad<T, BoxedTag> for B where B : Boxed<T> {
+    fn extract(&self) -> &T { self }
+    fn extend<U>(&self, mut f: impl FnMut(&B) -> U) -> B::Boxed<U> {
+        f(self).into()
+    }
+}
+
+/// Smart pointers are applicative by reference, with `lift2` applying `f` to
+/// the two pointees.
+impl<'a, T: 'a, B> Applicative<'a, T, BoxedTag> for B where B : Boxed<T> {
+    fn pure(x: &T) -> B where T: Clone { x.clone().into() }
+
+    fn lift2<U: 'a, V: 'a>(f: impl Fn(&'a T, &'a U) -> V,
+                           a: &'a B, b: &'a B::Boxed<U>) -> B::Boxed<V> {
+        f(a, b).into()
+    }
+}
+
 impl<T> FunctorOnce<T, BoxedTag> for Box<T> {
     fn fmap_once<U>(self, mut f: impl FnMut(T) -> U) -> Box<U> {
         f(*self).into()
     }
 }
 
+/// Shared pointers can only give up their value by cloning it if there are
+/// other references.
+impl<T: Clone> FunctorOnce<T, BoxedTag> for Rc<T> {
+    fn fmap_once<U>(self, mut f: impl FnMut(T) -> U) -> Rc<U> {
+        f(Rc::unwrap_or_clone(self)).into()
+    }
+}
+
+impl<T: Clone> FunctorOnce<T, BoxedTag> for Arc<T> {
+    fn fmap_once<U>(self, mut f: impl FnMut(T) -> U) -> Arc<U> {
+        f(Arc::unwrap_or_clone(self)).into()
+    }
+}
+
+impl<T> Apply<T, BoxedTag> for Box<T> {
+    fn lift2_once<U, V>(f: impl Fn(T, U) -> V, a: Box<T>, b: Box<U>)
+                        -> Box<V> {
+        Box::new(f(*a, *b))
+    }
+
+    fn call_once<A, U>(self, x: Box<A>) -> Box<U> where T: Fn(A) -> U {
+        Box::new(self(*x))
+    }
+}
+
+impl<T> ApplicativeOnce<T, BoxedTag> for Box<T> {
+    fn pure_once(x: T) -> Box<T> { Box::new(x) }
+}
+
+/// As for `fmap_once`, the values are cloned out of shared pointers with other
+/// references.
+impl<T: Clone> Apply<T, BoxedTag> for Rc<T> {
+    fn lift2_once<U, V>(f: impl Fn(T, U) -> V, a: Rc<T>, b: Rc<U>) -> Rc<V>
+        where U: Clone
+    {
+        Rc::new(f(Rc::unwrap_or_clone(a), Rc::unwrap_or_clone(b)))
+    }
+
+    fn call_once<A, U>(self, x: Rc<A>) -> Rc<U>
+        where T: Fn(A) -> U, A: Clone
+    {
+        Rc::new(Rc::unwrap_or_clone(self)(Rc::unwrap_or_clone(x)))
+    }
+}
+
+impl<T: Clone> ApplicativeOnce<T, BoxedTag> for Rc<T> {
+    fn pure_once(x: T) -> Rc<T> { Rc::new(x) }
+}
+
+impl<T: Clone> Apply<T, BoxedTag> for Arc<T> {
+    fn lift2_once<U, V>(f: impl Fn(T, U) -> V, a: Arc<T>, b: Arc<U>) -> Arc<V>
+        where U: Clone
+    {
+        Arc::new(f(Arc::unwrap_or_clone(a), Arc::unwrap_or_clone(b)))
+    }
+
+    fn call_once<A, U>(self, x: Arc<A>) -> Arc<U>
+        where T: Fn(A) -> U, A: Clone
+    {
+        Arc::new(Arc::unwrap_or_clone(self)(Arc::unwrap_or_clone(x)))
+    }
+}
+
+impl<T: Clone> ApplicativeOnce<T, BoxedTag> for Arc<T> {
+    fn pure_once(x: T) -> Arc<T> { Arc::new(x) }
+}
+
+impl<T> FunctorMutate<T, BoxedTag> for Box<T> {
+    fn fmutate(&mut self, mut f: impl FnMut(&mut T)) { f(self) }
+}
+
+/// Shared pointers are cloned first if not unique, as for `make_mut`, so other
+/// owners see no change.
+impl<T: Clone> FunctorMutate<T, BoxedTag> for Rc<T> {
+    fn fmutate(&mut self, mut f: impl FnMut(&mut T)) { f(Rc::make_mut(self)) }
+}
+
+impl<T: Clone> FunctorMutate<T, BoxedTag> for Arc<T> {
+    fn fmutate(&mut self, mut f: impl FnMut(&mut T)) { f(Arc::make_mut(self)) }
+}
+
 impl<T> Boxed<T> for Rc<T>  { type Boxed<U> = Rc<U>; }
+impl<T> Boxed<T> for Arc<T> { type Boxed<U> = Arc<U>; }
 impl<T> Boxed<T> for Box<T> { type Boxed<U> = Box<U>; }
 
+crate::coherent!(impl[T] Coherent<T, BoxedTag> for Rc<T> => Rc<V>);
+crate::coherent!(impl[T] Coherent<T, BoxedTag> for Arc<T> => Arc<V>);
+crate::coherent!(impl[T] Coherent<T, BoxedTag> for Box<T> => Box<V>);
+
 #[test]
 fn box_test() {
     let b1 = Box::new(27u32);
@@ -36,3 +143,57 @@ fn box_test() {
     assert_eq!(*b2, 54.0);
     assert_eq!(*b3, 81.0);
 }
+
+#[test]
+fn box_comonad() {
+    let r = Rc::new(String::from("abc"));
+    assert_eq!(r.extract(), "abc");
+    let n = r.extend(|s| Rc::strong_count(s) + s.len());
+    assert_eq!(*n, 4);
+    let d = Box::new(5).duplicate();
+    assert_eq!(**d, 5);
+}
+
+#[test]
+fn box_applicative() {
+    fn add<'a, B: Applicative<'a, u32, BoxedTag>>(
+        a: &'a B, b: &'a B::Functor<u32>) -> B::Functor<u32> {
+        B::lift2(|x, y| x + y, a, b)
+    }
+    assert_eq!(*add(&Rc::new(2), &Rc::new(3)), 5);
+    let a = Arc::new(4);
+    assert_eq!(*add(&a, &Arc::pure(&1)), 5);
+    assert_eq!(*Arc::new(String::from("x")).fmap_once(|s| s + "y"), "xy");
+
+    let b = Box::lift2_once(|x: u8, s: &str| s.repeat(x.into()),
+                            Box::new(2), Box::new("ab"));
+    assert_eq!(*b, "abab");
+    let f = Box::new(|x: u8| x * 3);
+    assert_eq!(*Apply::call_once(f, Box::new(5)), 15);
+
+    let shared = Rc::new(String::from("cd"));
+    let r = Rc::lift2_once(|x: u8, s: String| s.repeat(x.into()),
+                           Rc::new(3), shared.clone());
+    assert_eq!((r.as_str(), shared.as_str()), ("cdcdcd", "cd"));
+    assert_eq!(*Apply::call_once(Rc::new(|x: u8| x + 1), Rc::new(1)), 2);
+    let p = <Arc<u8> as ApplicativeOnce<_, BoxedTag>>::pure_once(6);
+    assert_eq!(*Arc::lift2_once(|x, y| x * y, p, Arc::new(7)), 42);
+    assert_eq!(*Apply::call_once(Arc::new(|x: u32| x * 2), a), 8);
+}
+
+#[test]
+fn box_fmutate() {
+    let mut b = Box::new(2);
+    let p: *const i32 = &*b;
+    b.fmutate(|x| *x *= 5);
+    assert_eq!((*b, &*b as *const i32), (10, p));
+
+    let mut r = Rc::new(vec![1]);
+    r.fmutate(|v| v.push(2));
+    let s = r.clone();
+    r.fmutate(|v| v.push(3));
+    assert_eq!((r.as_slice(), s.as_slice()), (&[1, 2, 3][..], &[1, 2][..]));
+    let mut a = Arc::new(1);
+    a.fmutate(|x| *x += 1);
+    assert_eq!(*a, 2);
+}
@@ -0,0 +1,153 @@
+//! Comonads, the dual of monads.
+//!
+//! Where a monad puts a value into a context, a comonad takes a value out of
+//! one, and `extend` lets a function see the whole context at every position.
+
+use crate::{Comp1, FunctorOnce, TypeMap};
+
+
+pub trait Comonad<T, Tag = ()> : TypeMap<T, Tag> {
+    /// W(T) → T
+    fn extract(&self) -> &T;
+
+    /// (W(T) → U) × W(T) → W(U)
+    ///
+    /// `f` is called with the context refocused at each position in turn.
+    fn extend<U>(&self, f: impl FnMut(&Self) -> U) -> Self::Functor<U>;
+
+    /// W(T) → W(W(T))
+    fn duplicate(&self) -> Self::Functor<Self> where Self: Clone {
+        self.extend(|w| w.clone())
+    }
+}
+
+
+/// The environment comonad: a value together with a read-only context.
+impl<E: Clone, T> Comonad<T, Comp1> for (E, T) {
+    fn extract(&self) -> &T { &self.1 }
+    fn extend<U>(&self, mut f: impl FnMut(&(E, T)) -> U) -> (E, U) {
+        (self.0.clone(), f(self))
+    }
+}
+
+
+/// Tag for an array with a focused position, `([T; N], usize)`.
+///
+/// As this is also a pair, method calls usually need the tag, e.g.,
+/// `Comonad::<_, Focus>::extend(&w, f)`.  Nothing checks the position, so
+/// prefer [`Focused`], which does.
+pub struct Focus;
+
+impl<T, const N: usize> TypeMap<T, Focus> for ([T; N], usize) {
+    type Functor<U> = ([U; N], usize);
+}
+
+impl<T, const N: usize> FunctorOnce<T, Focus> for ([T; N], usize) {
+    fn fmap_once<U>(self, f: impl FnMut(T) -> U) -> ([U; N], usize) {
+        (self.0.map(f), self.1)
+    }
+}
+
+/// `extend` sees the array focused on each position in turn.  `extract`
+/// panics if the position is not less than `N`.
+impl<T: Clone, const N: usize> Comonad<T, Focus> for ([T; N], usize) {
+    fn extract(&self) -> &T { &self.0[self.1] }
+    fn extend<U>(&self, mut f: impl FnMut(&([T; N], usize)) -> U)
+                 -> ([U; N], usize) {
+        let mut w = self.clone();
+        let out = std::array::from_fn(|i| { w.1 = i; f(&w) });
+        (out, self.1)
+    }
+}
+
+crate::coherent!(
+    impl[T, const N: usize] Coherent<T, Focus> for ([T; N], usize)
+        => ([V; N], usize));
+
+
+/// An array with a focused position.
+///
+/// The position is checked on construction, so it is always in bounds, and an
+/// empty array cannot be focused at all.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Focused<T, const N: usize> {
+    array: [T; N],
+    focus: usize,
+}
+
+impl<T, const N: usize> Focused<T, N> {
+    /// `None` unless `focus < N`.
+    pub fn new(array: [T; N], focus: usize) -> Option<Focused<T, N>> {
+        (focus < N).then_some(Focused { array, focus })
+    }
+
+    pub fn array(&self) -> &[T; N] { &self.array }
+    pub fn focus(&self) -> usize { self.focus }
+    pub fn into_array(self) -> [T; N] { self.array }
+}
+
+impl<T, const N: usize> TypeMap<T> for Focused<T, N> {
+    type Functor<U> = Focused<U, N>;
+}
+
+impl<T, const N: usize> FunctorOnce<T> for Focused<T, N> {
+    fn fmap_once<U>(self, f: impl FnMut(T) -> U) -> Focused<U, N> {
+        Focused { array: self.array.map(f), focus: self.focus }
+    }
+}
+
+/// `extend` sees the array focused on each position in turn, so this gives
+/// sliding window computations.
+impl<T: Clone, const N: usize> Comonad<T> for Focused<T, N> {
+    fn extract(&self) -> &T { &self.array[self.focus] }
+    fn extend<U>(&self, mut f: impl FnMut(&Focused<T, N>) -> U)
+                 -> Focused<U, N> {
+        let mut w = self.clone();
+        let array = std::array::from_fn(|i| { w.focus = i; f(&w) });
+        Focused { array, focus: self.focus }
+    }
+}
+
+crate::coherent!(
+    impl[T, const N: usize] Coherent<T> for Focused<T, N> => Focused<V, N>);
+
+
+#[test]
+fn env_test() {
+    let e = ("ctx", 3);
+    assert_eq!(*Comonad::<_, Comp1>::extract(&e), 3);
+    let x = Comonad::<_, Comp1>::extend(&e, |(c, v)| c.len() + v);
+    assert_eq!(x, ("ctx", 6));
+    let d = Comonad::<_, Comp1>::duplicate(&e);
+    assert_eq!(d, ("ctx", ("ctx", 3)));
+}
+
+#[test]
+fn sliding_window() {
+    let w = Focused::new([1, 2, 3, 4, 5], 2).unwrap();
+    assert_eq!(*w.extract(), 3);
+    // Sum of each element with its neighbours.
+    let sums = w.extend(|w| {
+        let (a, i) = (w.array(), w.focus());
+        a[i.saturating_sub(1) ..= (i + 1).min(a.len() - 1)].iter().sum::<i32>()
+    });
+    assert_eq!(sums, Focused::new([3, 6, 9, 12, 9], 2).unwrap());
+    let d = w.duplicate();
+    assert_eq!(d.array()[4], Focused::new([1, 2, 3, 4, 5], 4).unwrap());
+    assert_eq!(d.focus(), 2);
+
+    assert_eq!(Focused::new([1, 2, 3], 5), None);
+    assert_eq!(Focused::<u8, 0>::new([], 0), None);
+}
+
+#[test]
+fn focus_pair() {
+    let w = ([1, 2, 3], 1usize);
+    assert_eq!(*Comonad::<_, Focus>::extract(&w), 2);
+    let d = Comonad::<_, Focus>::extend(&w, |&(a, i)| a[i] * 10);
+    assert_eq!(d, ([10, 20, 30], 1));
+    let out_of_range = ([1], 5usize);
+    let r = std::panic::catch_unwind(
+        || *Comonad::<_, Focus>::extract(&out_of_range));
+    assert!(r.is_err());
+}
@@ -0,0 +1,207 @@
+//! Functors that can drop elements.
+//!
+//! `fmap` preserves the shape, while `filter_map` may remove elements, giving
+//! the same family of container.  For maps, a dropped value drops its key too.
+//!
+//! A `NonEmpty` could be filtered down to nothing, so it is not filterable:
+//!
+//! ```compile_fail
+//! # use functor::{Filterable, Mapped, NonEmpty};
+//! Filterable::<_, Mapped>::filter(NonEmpty::new(1), |x| *x > 10);
+//! ```
+
+use crate::{ApplicativeOnce, Coherent, FunctorOnce, Mapped, TypeMap};
+use crate::bimapable::{BiMapable, Derived};
+
+use std::collections::{BTreeMap, HashMap, LinkedList, VecDeque};
+use std::hash::{BuildHasher, Hash};
+
+
+pub trait Filterable<T, Tag = ()> : TypeMap<T, Tag> {
+    /// Map, keeping only the `Some` results.
+    fn filter_map<U>(self, f: impl FnMut(T) -> Option<U>) -> Self::Functor<U>;
+
+    /// Split by the results of `f`, `Ok` to the left and `Err` to the right.
+    fn partition_map<U, V>(self, f: impl FnMut(T) -> Result<U, V>)
+                           -> (Self::Functor<U>, Self::Functor<V>);
+
+    /// Keep only the elements satisfying `p`.
+    fn filter(self, mut p: impl FnMut(&T) -> bool) -> Self
+        where Self: Sized + TypeMap<T, Tag, Functor<T> = Self>
+    {
+        self.filter_map::<T>(|x| if p(&x) { Some(x) } else { None })
+    }
+}
+
+/// `filter_map` with an effect, through the applicative `F`.
+///
+/// The effects are run in order, and combined with `lift2_once`, so, as for
+/// that, the intermediate results must be `Clone`.
+pub trait Witherable<T, Tag = ()> : Filterable<T, Tag> {
+    fn wither<U: Clone, ATag, F, G>(self, f: impl FnMut(T) -> F)
+                                    -> G::Functor<Self::Functor<U>>
+        where F: ApplicativeOnce<Option<U>, ATag>
+                 + FunctorOnce<Option<U>, ATag, Functor<Vec<U>> = G>,
+              G: ApplicativeOnce<Vec<U>, ATag, Functor<Option<U>> = F>
+                 + Coherent<Vec<U>, ATag>;
+}
+
+
+/// Collections that may be empty.  Not blanket over `Mapable`, as that
+/// includes `NonEmpty`.
+macro_rules! filterable {
+    ($($c:ident)*) => {$(
+        impl<T> Filterable<T, Mapped> for $c<T> {
+            fn filter_map<U>(self, f: impl FnMut(T) -> Option<U>) -> $c<U> {
+                self.into_iter().filter_map(f).collect()
+            }
+            fn partition_map<U, V>(self, mut f: impl FnMut(T) -> Result<U, V>)
+                                   -> ($c<U>, $c<V>) {
+                let (oks, errs) = partition(self, &mut f);
+                (oks.into_iter().collect(), errs.into_iter().collect())
+            }
+        }
+
+        impl<T> Witherable<T, Mapped> for $c<T> {
+            fn wither<U: Clone, ATag, F, G>(self, mut f: impl FnMut(T) -> F)
+                                            -> G::Functor<$c<U>>
+                where F: ApplicativeOnce<Option<U>, ATag>
+                         + FunctorOnce<Option<U>, ATag, Functor<Vec<U>> = G>,
+                      G: ApplicativeOnce<Vec<U>, ATag, Functor<Option<U>> = F>
+                         + Coherent<Vec<U>, ATag>
+            {
+                let mut acc = G::pure_once(Vec::new());
+                for x in self {
+                    acc = G::lift2_once::<Option<U>, Vec<U>>(
+                        |mut v, y| { v.extend(y); v }, acc, f(x));
+                }
+                acc.fmap_once::<$c<U>>(|v| v.into_iter().collect())
+            }
+        }
+    )*};
+}
+
+filterable!(Vec VecDeque LinkedList);
+
+fn partition<T, U, V>(xs: impl IntoIterator<Item = T>,
+                      f: &mut impl FnMut(T) -> Result<U, V>)
+                      -> (Vec<U>, Vec<V>) {
+    let mut oks = Vec::new();
+    let mut errs = Vec::new();
+    for x in xs {
+        match f(x) {
+            Ok(u) => oks.push(u),
+            Err(v) => errs.push(v),
+        }
+    }
+    (oks, errs)
+}
+
+
+impl<T> Filterable<T> for Option<T> {
+    fn filter_map<U>(self, f: impl FnMut(T) -> Option<U>) -> Option<U> {
+        self.and_then(f)
+    }
+    fn partition_map<U, V>(self, mut f: impl FnMut(T) -> Result<U, V>)
+                           -> (Option<U>, Option<V>) {
+        match self.map(&mut f) {
+            None => (None, None),
+            Some(Ok(u)) => (Some(u), None),
+            Some(Err(v)) => (None, Some(v)),
+        }
+    }
+}
+
+impl<T> Witherable<T> for Option<T> {
+    fn wither<U: Clone, ATag, F, G>(self, mut f: impl FnMut(T) -> F) -> F
+        where F: ApplicativeOnce<Option<U>, ATag>
+                 + FunctorOnce<Option<U>, ATag, Functor<Vec<U>> = G>,
+              G: ApplicativeOnce<Vec<U>, ATag, Functor<Option<U>> = F>
+                 + Coherent<Vec<U>, ATag>
+    {
+        match self {
+            None => F::pure_once(None),
+            Some(x) => f(x),
+        }
+    }
+}
+
+
+/// The results keep the hasher of `self`.
+impl<K: Eq + Hash, T, S: BuildHasher + Clone> Filterable<T, Derived<K, ()>>
+    for HashMap<K, T, S>
+{
+    fn filter_map<U>(self, mut f: impl FnMut(T) -> Option<U>)
+                     -> HashMap<K, U, S> {
+        let mut out = self.empty_like();
+        out.extend(self.into_iter().filter_map(|(k, v)| Some((k, f(v)?))));
+        out
+    }
+    fn partition_map<U, V>(self, mut f: impl FnMut(T) -> Result<U, V>)
+                           -> (HashMap<K, U, S>, HashMap<K, V, S>) {
+        let (mut l, mut r) = (self.empty_like(), self.empty_like());
+        let (oks, errs) = partition(self, &mut |(k, v)| match f(v) {
+            Ok(u) => Ok((k, u)),
+            Err(v) => Err((k, v)),
+        });
+        l.extend(oks);
+        r.extend(errs);
+        (l, r)
+    }
+}
+
+impl<K: Ord, T> Filterable<T, Derived<K, ()>> for BTreeMap<K, T> {
+    fn filter_map<U>(self, mut f: impl FnMut(T) -> Option<U>)
+                     -> BTreeMap<K, U> {
+        self.into_iter().filter_map(|(k, v)| Some((k, f(v)?))).collect()
+    }
+    fn partition_map<U, V>(self, mut f: impl FnMut(T) -> Result<U, V>)
+                           -> (BTreeMap<K, U>, BTreeMap<K, V>) {
+        let (oks, errs) = partition(self, &mut |(k, v)| match f(v) {
+            Ok(u) => Ok((k, u)),
+            Err(v) => Err((k, v)),
+        });
+        (oks.into_iter().collect(), errs.into_iter().collect())
+    }
+}
+
+
+#[test]
+fn filter_test() {
+    let v = vec![1, 2, 3, 4, 5];
+    assert_eq!(v.clone().filter_map(|x| (x % 2 == 1).then_some(x * 10)),
+               [10, 30, 50]);
+    assert_eq!(v.clone().filter(|x| *x > 3), [4, 5]);
+    let (l, r) = LinkedList::from([1, 2, 3]).partition_map(
+        |x| if x == 2 { Ok(x.to_string()) } else { Err(x) });
+    assert_eq!((l, r), (LinkedList::from(["2".into()]),
+                        LinkedList::from([1, 3])));
+
+    assert_eq!(Some(3).filter_map(|x| (x > 5).then_some(x)), None);
+    assert_eq!(Some(3).partition_map(Err::<(), _>), (None, Some(3)));
+
+    let m = BTreeMap::from([("a", 1), ("b", -2), ("c", 3)]);
+    assert_eq!(m.clone().filter(|x| *x > 0),
+               BTreeMap::from([("a", 1), ("c", 3)]));
+    let (pos, neg) = m.partition_map(|x| if x > 0 { Ok(x) } else { Err(-x) });
+    assert_eq!((pos.len(), neg["b"]), (2, 2));
+
+    let h: HashMap<u8, &str> = HashMap::from([(1, "1"), (2, "x")]);
+    assert_eq!(h.filter_map(|s| s.parse::<u8>().ok()),
+               HashMap::from([(1, 1)]));
+}
+
+#[test]
+fn wither_test() {
+    let parse = |s: &str| -> Option<Option<u32>> {
+        if s == "bad" { None } else { Some(s.parse().ok()) }
+    };
+    assert_eq!(vec!["1", "x", "3"].wither(parse), Some(vec![1, 3]));
+    assert_eq!(vec!["1", "bad", "3"].wither(parse), None);
+    assert_eq!(Some("x").wither(parse), Some(None));
+
+    let r: Result<Vec<u32>, String> = vec![4, 5, 6].wither(|x| {
+        if x < 6 { Ok((x % 2 == 0).then_some(x)) } else { Err(x.to_string()) }
+    });
+    assert_eq!(r, Err("6".into()));
+}
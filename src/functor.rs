@@ -22,6 +22,37 @@ pub trait TypeMap<T, Tag = ()> {
 /// `Self` is the type resulting from applying the functor to the type `T`.
 pub trait FunctorOnce<T, Tag = ()> : TypeMap<T, Tag> {
     fn fmap_once<U>(self, f: impl FnMut(Self::Item) -> U) -> Self::Functor<U>;
+
+    /// Discard the values, keeping the shape.
+    fn void(self) -> Self::Functor<()> where Self: Sized {
+        self.fmap_once(|_| ())
+    }
+
+    /// Replace every value with a copy of `x`.
+    fn fmap_const<U: Clone>(self, x: U) -> Self::Functor<U>
+        where Self: Sized
+    {
+        self.fmap_once(|_| x.clone())
+    }
+
+    /// Pair each value with the result of `f` on it.
+    fn fproduct<U>(self, mut f: impl FnMut(&Self::Item) -> U)
+                   -> Self::Functor<(Self::Item, U)> where Self: Sized {
+        self.fmap_once(|x| { let u = f(&x); (x, u) })
+    }
+
+    /// Split a functor of pairs into a pair of functors.
+    ///
+    /// This maps a clone of `self` for the first components, and then `self`
+    /// for the second, so it clones the whole container, both halves of every
+    /// pair included, and drops half of each copy.  Where the functor can be
+    /// iterated, e.g., `Vec`, `Iterator::unzip` does it in one pass instead.
+    /// Named so as not to be shadowed by `Option::unzip`.
+    fn funzip<A, B>(self) -> (Self::Functor<A>, Self::Functor<B>)
+        where Self: Sized + Clone + TypeMap<T, Tag, Item = (A, B)>
+    {
+        (self.clone().fmap_once(|(a, _)| a), self.fmap_once(|(_, b)| b))
+    }
 }
 
 
@@ -35,8 +66,12 @@ pub trait FunctorMut<'a, T, Tag = ()> : TypeMap<T, Tag> {
     /// Functor map while mutating the original.
     fn fmap_mut<U>(&'a mut self, f: impl FnMut(&mut Self::Item) -> U)
                    -> Self::Functor<U>;
-    // / Mutate the original but discard output.
-    // fn fmutate(&mut self, f: impl FnMut(&mut Self::Item));
+}
+
+/// Trait for mapping `T -> T` in place, keeping the container and so with no
+/// allocation.
+pub trait FunctorMutate<T, Tag = ()> : TypeMap<T, Tag> {
+    fn fmutate(&mut self, f: impl FnMut(&mut Self::Item));
 }
 
 
@@ -48,6 +83,16 @@ impl<T, const N: usize> FunctorOnce<T> for [T; N] {
     fn fmap_once<U>(self, f: impl FnMut(T) -> U) -> [U; N] { self.map(f) }
 }
 
+impl<'a, T, const N: usize> FunctorMut<'a, T> for [T; N] {
+    fn fmap_mut<U>(&mut self, f: impl FnMut(&mut T) -> U) -> [U; N] {
+        self.each_mut().map(f)
+    }
+}
+
+impl<T, const N: usize> FunctorMutate<T> for [T; N] {
+    fn fmutate(&mut self, f: impl FnMut(&mut T)) { self.iter_mut().for_each(f) }
+}
+
 /// Pairs are functorial in both components.  Use a tag to indicate which.
 pub struct Comp0;
 pub struct Comp1;
@@ -78,8 +123,14 @@ impl<'a, A: Copy, T: 'a> Functor<'a, T, Comp1> for (A, T) {
 impl<'a, A: Copy, T> FunctorMut<'a, T, Comp1> for (A, T) {
     fn fmap_mut<U>(&mut self, mut f: impl FnMut(&mut T) -> U) -> (A, U) {
         (self.0, f(&mut self.1)) }
-    // fn fmutate(&mut self, mut f: impl FnMut(&mut T)) {
-    // f(&mut self.1) }
+}
+
+impl<T, B> FunctorMutate<T, Comp0> for (T, B) {
+    fn fmutate(&mut self, mut f: impl FnMut(&mut T)) { f(&mut self.0) }
+}
+
+impl<A, T> FunctorMutate<T, Comp1> for (A, T) {
+    fn fmutate(&mut self, mut f: impl FnMut(&mut T)) { f(&mut self.1) }
 }
 
 impl<T> TypeMap<T> for Option<T> {
@@ -100,6 +151,9 @@ impl<'a, T> FunctorMut<'a, T> for Option<T> {
         Some(f(self.as_mut()?))
     }
 }
+impl<T> FunctorMutate<T> for Option<T> {
+    fn fmutate(&mut self, f: impl FnMut(&mut T)) { self.iter_mut().for_each(f) }
+}
 
 /// Because we cannot force the expected type equalities, second best is to have
 /// conversion functions that _should_ always be the identity in reality.
@@ -115,11 +169,26 @@ pub trait Coherent<T, Tag = ()> : TypeMap<T, Tag, Functor<T> = Self>
     //fn inject(x : Self) -> Self::Functor<T>;
 }
 
-impl<A,T> Coherent<T, Comp1> for (A,T) {
-    fn cohere<U,V>(x : (A,V)) -> (A,V) { x }
-    // fn inject(x : (A,T)) -> (A,T) { x }
+/// Implement `Coherent` for a type where `Functor<U>` is just the same type
+/// constructor at `U`, so that `cohere` is the identity.  The type after `=>`
+/// is the result at the method's type parameter `V`, e.g.,
+///
+/// `coherent!(impl[T] Coherent<T> for Option<T> => Option<V>);`
+#[macro_export]
+macro_rules! coherent {
+    (impl[$($g:tt)*] Coherent<$t:ty $(, $tag:ty)?> for $s:ty => $v:ty) => {
+        impl<$($g)*> $crate::functor::Coherent<$t $(, $tag)?> for $s {
+            fn cohere<U, V>(x: $v) -> $v { x }
+        }
+    };
 }
 
+coherent!(impl[A, T] Coherent<T, Comp1> for (A, T) => (A, V));
+coherent!(impl[T, B] Coherent<T, Comp0> for (T, B) => (V, B));
+coherent!(impl[T] Coherent<T> for Option<T> => Option<V>);
+coherent!(impl[T, const N: usize] Coherent<T> for [T; N] => [V; N]);
+
+
 
 #[test]
 fn array1() {
@@ -127,3 +196,42 @@ fn array1() {
     let vv = v.fmap_once(|x| x.to_string());
     assert_eq!(vv, ["1", "2", "3"]);
 }
+
+#[test]
+fn cohere_generic() {
+    /// Map twice in generic code, getting back to the expected type.
+    fn twice<T, Tag, C: Coherent<T, Tag> + FunctorOnce<T, Tag, Item = T>>(
+        c: C, f: impl FnMut(T) -> T) -> C
+        where C::Functor<T>: FunctorOnce<T, Tag, Item = T>
+    {
+        let mut f = f;
+        let once = c.fmap_once(&mut f);
+        C::cohere::<T, T>(once.fmap_once(f))
+    }
+    assert_eq!(twice(Some(3), |x| x + 1), Some(5));
+    assert_eq!(twice([1, 2], |x| x * 3), [9, 18]);
+    assert_eq!(twice::<_, Comp0, _>((1, "b"), |x| x - 1), (-1, "b"));
+}
+
+#[test]
+fn combinators() {
+    assert_eq!([1, 2].void(), [(), ()]);
+    assert_eq!(Some(1).fmap_const("x"), Some("x"));
+    assert_eq!([3, 4].fproduct(|x| x * x), [(3, 9), (4, 16)]);
+    assert_eq!([(1, 'a'), (2, 'b')].funzip(), ([1, 2], ['a', 'b']));
+    assert_eq!(None::<(u8, char)>.funzip(), (None, None));
+}
+
+#[test]
+fn fmutate_test() {
+    let mut a = [1, 2, 3];
+    a.fmutate(|x| *x *= 2);
+    assert_eq!(a, [2, 4, 6]);
+    let mut p = (1, "b");
+    FunctorMutate::<_, Comp0>::fmutate(&mut p, |x| *x += 1);
+    FunctorMutate::<_, Comp1>::fmutate(&mut p, |s| *s = "c");
+    assert_eq!(p, (2, "c"));
+    let mut o = Some(String::from("x"));
+    o.fmutate(|s| s.push('y'));
+    assert_eq!(o.as_deref(), Some("xy"));
+}
@@ -0,0 +1,203 @@
+//! Directed graphs with data on the nodes and edges.
+//!
+//! A `Graph<N, E>` is a bifunctor on the node and edge data.  The topology is
+//! kept behind an `Arc`, so mapping shares it between the original and the
+//! result rather than copying it, and node and edge indices are valid in both.
+//! Graphs only grow, so indices are never invalidated.
+
+use crate::bifunctor::{BiFunctor, BiFunctorMut, BiFunctorOnce, BiTypeMap};
+
+use std::ops::{Index, IndexMut};
+use std::sync::Arc;
+
+
+/// The index of a node in a `Graph`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeIx(pub usize);
+
+/// The index of an edge in a `Graph`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EdgeIx(pub usize);
+
+/// The shape of the graph, without the data.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Topology {
+    /// The source and target of each edge.
+    ends: Vec<(NodeIx, NodeIx)>,
+    /// The outgoing edges of each node.
+    out: Vec<Vec<EdgeIx>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Graph<N, E> {
+    nodes: Vec<N>,
+    edges: Vec<E>,
+    topology: Arc<Topology>,
+}
+
+impl<N, E> Default for Graph<N, E> {
+    fn default() -> Graph<N, E> { Graph::new() }
+}
+
+impl<N, E> Graph<N, E> {
+    pub fn new() -> Graph<N, E> {
+        Graph { nodes: Vec::new(), edges: Vec::new(),
+                topology: Arc::default() }
+    }
+
+    pub fn add_node(&mut self, n: N) -> NodeIx {
+        Arc::make_mut(&mut self.topology).out.push(Vec::new());
+        self.nodes.push(n);
+        NodeIx(self.nodes.len() - 1)
+    }
+
+    /// Add an edge from `a` to `b`.  Panics if either is not a node.
+    pub fn add_edge(&mut self, a: NodeIx, b: NodeIx, e: E) -> EdgeIx {
+        assert!(a.0 < self.nodes.len() && b.0 < self.nodes.len(),
+                "edge to a missing node");
+        let ix = EdgeIx(self.edges.len());
+        let topology = Arc::make_mut(&mut self.topology);
+        topology.ends.push((a, b));
+        topology.out[a.0].push(ix);
+        self.edges.push(e);
+        ix
+    }
+
+    pub fn node_count(&self) -> usize { self.nodes.len() }
+
+    pub fn edge_count(&self) -> usize { self.edges.len() }
+
+    /// The source and target of an edge.
+    pub fn ends(&self, e: EdgeIx) -> (NodeIx, NodeIx) {
+        self.topology.ends[e.0]
+    }
+
+    /// The outgoing edges of a node, in the order they were added.
+    pub fn out_edges(&self, n: NodeIx) -> &[EdgeIx] { &self.topology.out[n.0] }
+
+    /// The targets of the outgoing edges of a node.
+    pub fn successors(&self, n: NodeIx) -> impl Iterator<Item = NodeIx> + '_ {
+        self.out_edges(n).iter().map(|&e| self.ends(e).1)
+    }
+
+    pub fn nodes(&self) -> &[N] { &self.nodes }
+
+    pub fn edges(&self) -> &[E] { &self.edges }
+
+    /// Whether `self` and `other` share their topology, e.g., because one was
+    /// mapped from the other.
+    pub fn same_topology<M, F>(&self, other: &Graph<M, F>) -> bool {
+        Arc::ptr_eq(&self.topology, &other.topology)
+    }
+}
+
+impl<N, E> Index<NodeIx> for Graph<N, E> {
+    type Output = N;
+    fn index(&self, n: NodeIx) -> &N { &self.nodes[n.0] }
+}
+
+impl<N, E> IndexMut<NodeIx> for Graph<N, E> {
+    fn index_mut(&mut self, n: NodeIx) -> &mut N { &mut self.nodes[n.0] }
+}
+
+impl<N, E> Index<EdgeIx> for Graph<N, E> {
+    type Output = E;
+    fn index(&self, e: EdgeIx) -> &E { &self.edges[e.0] }
+}
+
+impl<N, E> IndexMut<EdgeIx> for Graph<N, E> {
+    fn index_mut(&mut self, e: EdgeIx) -> &mut E { &mut self.edges[e.0] }
+}
+
+/// Equal data and equal topology.
+impl<N: PartialEq, E: PartialEq> PartialEq for Graph<N, E> {
+    fn eq(&self, other: &Graph<N, E>) -> bool {
+        self.nodes == other.nodes && self.edges == other.edges
+            && (self.same_topology(other) || self.topology == other.topology)
+    }
+}
+
+impl<N: Eq, E: Eq> Eq for Graph<N, E> {}
+
+
+impl<N, E> BiTypeMap<N, E> for Graph<N, E> {
+    type BiFunctor<T, U> = Graph<T, U>;
+}
+
+impl<N, E> BiFunctorOnce<N, E> for Graph<N, E> {
+    fn fmap_once2<T, U>(self, f: impl FnMut(N) -> T, g: impl FnMut(E) -> U)
+                        -> Graph<T, U> {
+        Graph { nodes: self.nodes.into_iter().map(f).collect(),
+                edges: self.edges.into_iter().map(g).collect(),
+                topology: self.topology }
+    }
+}
+
+impl<N, E> BiFunctor<N, E> for Graph<N, E> {
+    fn fmap2<T, U>(&self, f: impl FnMut(&N) -> T, g: impl FnMut(&E) -> U)
+                   -> Graph<T, U> {
+        Graph { nodes: self.nodes.iter().map(f).collect(),
+                edges: self.edges.iter().map(g).collect(),
+                topology: self.topology.clone() }
+    }
+}
+
+impl<N, E> BiFunctorMut<N, E> for Graph<N, E> {
+    fn fmap_mut2<T, U>(&mut self, f: impl FnMut(&mut N) -> T,
+                       g: impl FnMut(&mut E) -> U) -> Graph<T, U> {
+        Graph { nodes: self.nodes.iter_mut().map(f).collect(),
+                edges: self.edges.iter_mut().map(g).collect(),
+                topology: self.topology.clone() }
+    }
+}
+
+crate::bicoherent!(impl[N, E] BiCoherent<N, E> for Graph<N, E> => Graph<U, V>);
+
+
+#[test]
+fn graph_test() {
+    use crate::{FunctorOnce, Functor};
+    use crate::bifunctor::{Proj0, Proj1};
+
+    let mut g = Graph::new();
+    let a = g.add_node("a");
+    let b = g.add_node("b");
+    let c = g.add_node("c");
+    let ab = g.add_edge(a, b, 1);
+    let ac = g.add_edge(a, c, 2);
+    g.add_edge(c, a, 3);
+    assert_eq!(g.successors(a).collect::<Vec<_>>(), [b, c]);
+
+    let h = g.fmap2(|n| n.to_uppercase(), |e| e * 10);
+    assert!(h.same_topology(&g));
+    assert_eq!((h[c].as_str(), h[ac], h.ends(ab)), ("C", 20, (a, b)));
+
+    let weights = Functor::<_, Proj1<&str>>::fmap(&g, |e| *e as f64 / 2.0);
+    assert_eq!(weights.edges(), [0.5, 1.0, 1.5]);
+    let mut lens = FunctorOnce::<_, Proj0<i32>>::fmap_once(g, str::len);
+    assert!(lens.same_topology(&h));
+    lens[b] = 5;
+    assert_eq!(lens.nodes(), [1, 5, 1]);
+
+    // Growing a shared graph leaves the other unchanged.
+    let d = lens.add_node(0);
+    lens.add_edge(b, d, 4);
+    assert!(!lens.same_topology(&h));
+    assert_eq!((h.node_count(), h.edge_count()), (3, 3));
+    assert_eq!(lens.successors(b).collect::<Vec<_>>(), [d]);
+}
+
+#[test]
+fn graph_mut() {
+    let mut g: Graph<u32, u32> = Graph::new();
+    let a = g.add_node(1);
+    let b = g.add_node(2);
+    g.add_edge(a, b, 7);
+    let old = g.fmap_mut2(|n| { *n += 1; *n - 1 }, std::mem::take);
+    assert_eq!((old.nodes(), old.edges()), (&[1, 2][..], &[7][..]));
+    assert_eq!((g.nodes(), g.edges()), (&[2, 3][..], &[0][..]));
+    assert_eq!(old.fmap_once2(|n| n + 1, |_| 0), g);
+
+    let h = std::thread::spawn(move || g.fmap_once2(|n| n * 2, |e| e)).join();
+    assert_eq!(h.unwrap().nodes(), [4, 6]);
+}
@@ -0,0 +1,146 @@
+//! Mapping with the position or key of each element passed to the closure.
+//!
+//! The plain functor maps hide where an element is.  These give sequences the
+//! index, and maps the key, each in consuming, by reference and by mutable
+//! reference variants.  The result types are those of the corresponding
+//! functor, so `Tag` is as for that.
+
+use crate::{Mapped, NonEmpty, TypeMap};
+use crate::bimapable::{BiMapable, Derived};
+
+use std::collections::{BTreeMap, HashMap, LinkedList, VecDeque};
+use std::hash::{BuildHasher, Hash};
+
+
+/// Mapping with the position of each element.
+pub trait FunctorWithIndex<T, Tag = ()> : TypeMap<T, Tag> {
+    fn fmap_with_index_once<U>(self, f: impl FnMut(usize, T) -> U)
+                               -> Self::Functor<U>;
+
+    fn fmap_with_index<U>(&self, f: impl FnMut(usize, &T) -> U)
+                          -> Self::Functor<U>;
+
+    fn fmap_with_index_mut<U>(&mut self, f: impl FnMut(usize, &mut T) -> U)
+                              -> Self::Functor<U>;
+}
+
+/// Mapping with the key of each value.  The keys themselves are unchanged, so
+/// the by reference variants clone them.
+pub trait FunctorWithKey<K, T, Tag = ()> : TypeMap<T, Derived<K, Tag>> {
+    fn fmap_with_key_once<U>(self, f: impl FnMut(&K, T) -> U)
+                             -> Self::Functor<U>;
+
+    fn fmap_with_key<U>(&self, f: impl FnMut(&K, &T) -> U) -> Self::Functor<U>
+        where K: Clone;
+
+    fn fmap_with_key_mut<U>(&mut self, f: impl FnMut(&K, &mut T) -> U)
+                            -> Self::Functor<U>
+        where K: Clone;
+}
+
+
+macro_rules! with_index {
+    ($($c:ident)*) => {$(
+        impl<T> FunctorWithIndex<T, Mapped> for $c<T> {
+            fn fmap_with_index_once<U>(self, mut f: impl FnMut(usize, T) -> U)
+                                       -> $c<U> {
+                self.into_iter().enumerate().map(|(i, x)| f(i, x)).collect()
+            }
+            fn fmap_with_index<U>(&self, mut f: impl FnMut(usize, &T) -> U)
+                                  -> $c<U> {
+                self.iter().enumerate().map(|(i, x)| f(i, x)).collect()
+            }
+            fn fmap_with_index_mut<U>(
+                &mut self, mut f: impl FnMut(usize, &mut T) -> U) -> $c<U> {
+                self.iter_mut().enumerate().map(|(i, x)| f(i, x)).collect()
+            }
+        }
+    )*};
+}
+
+with_index!(Vec VecDeque LinkedList NonEmpty);
+
+impl<T, const N: usize> FunctorWithIndex<T> for [T; N] {
+    fn fmap_with_index_once<U>(self, mut f: impl FnMut(usize, T) -> U)
+                               -> [U; N] {
+        let mut i = 0;
+        self.map(|x| { i += 1; f(i - 1, x) })
+    }
+    fn fmap_with_index<U>(&self, mut f: impl FnMut(usize, &T) -> U) -> [U; N] {
+        std::array::from_fn(|i| f(i, &self[i]))
+    }
+    fn fmap_with_index_mut<U>(&mut self, mut f: impl FnMut(usize, &mut T) -> U)
+                              -> [U; N] {
+        let mut i = 0;
+        self.each_mut().map(|x| { i += 1; f(i - 1, x) })
+    }
+}
+
+
+/// The result keeps the hasher and capacity, as for `fmap`.
+impl<K: Eq + Hash, T, S: BuildHasher + Clone> FunctorWithKey<K, T>
+    for HashMap<K, T, S>
+{
+    fn fmap_with_key_once<U>(self, mut f: impl FnMut(&K, T) -> U)
+                             -> HashMap<K, U, S> {
+        let mut out = self.empty_like();
+        out.extend(self.into_iter().map(|(k, v)| { let u = f(&k, v); (k, u) }));
+        out
+    }
+    fn fmap_with_key<U>(&self, mut f: impl FnMut(&K, &T) -> U)
+                        -> HashMap<K, U, S> where K: Clone {
+        let mut out = self.empty_like();
+        out.extend(self.iter().map(|(k, v)| (k.clone(), f(k, v))));
+        out
+    }
+    fn fmap_with_key_mut<U>(&mut self, mut f: impl FnMut(&K, &mut T) -> U)
+                            -> HashMap<K, U, S> where K: Clone {
+        let mut out = self.empty_like();
+        out.extend(self.iter_mut().map(|(k, v)| (k.clone(), f(k, v))));
+        out
+    }
+}
+
+impl<K: Ord, T> FunctorWithKey<K, T> for BTreeMap<K, T> {
+    fn fmap_with_key_once<U>(self, mut f: impl FnMut(&K, T) -> U)
+                             -> BTreeMap<K, U> {
+        self.into_iter().map(|(k, v)| { let u = f(&k, v); (k, u) }).collect()
+    }
+    fn fmap_with_key<U>(&self, mut f: impl FnMut(&K, &T) -> U)
+                        -> BTreeMap<K, U> where K: Clone {
+        self.iter().map(|(k, v)| (k.clone(), f(k, v))).collect()
+    }
+    fn fmap_with_key_mut<U>(&mut self, mut f: impl FnMut(&K, &mut T) -> U)
+                            -> BTreeMap<K, U> where K: Clone {
+        self.iter_mut().map(|(k, v)| (k.clone(), f(k, v))).collect()
+    }
+}
+
+
+#[test]
+fn with_index() {
+    let v = vec!["a", "b", "c"];
+    assert_eq!(v.fmap_with_index(|i, s| format!("{i}{s}")), ["0a", "1b", "2c"]);
+    let d: VecDeque<_> = v.fmap_with_index_once(|i, s| (i, s)).into();
+    assert_eq!(d.fmap_with_index_once(|i, p| i == p.0), [true; 3]);
+
+    let mut a = [10, 20, 30];
+    let old = a.fmap_with_index_mut(|i, x| std::mem::replace(x, i));
+    assert_eq!((a, old), ([0, 1, 2], [10, 20, 30]));
+    assert_eq!(old.fmap_with_index(|i, x| x + i), [10, 21, 32]);
+    assert_eq!(old.fmap_with_index_once(|i, x| x * i), [0, 20, 60]);
+}
+
+#[test]
+fn with_key() {
+    let mut m = BTreeMap::from([(1, 10), (2, 20)]);
+    assert_eq!(m.fmap_with_key(|k, v| k + v),
+               BTreeMap::from([(1, 11), (2, 22)]));
+    let n = m.fmap_with_key_mut(|k, v| { *v *= k; *v > 10 });
+    assert_eq!(n, BTreeMap::from([(1, false), (2, true)]));
+    assert_eq!(m, BTreeMap::from([(1, 10), (2, 40)]));
+
+    let h: HashMap<&str, usize> = HashMap::from([("ab", 1), ("cde", 2)]);
+    let h = h.fmap_with_key_once(|k, v| k.len() * v);
+    assert_eq!(h, HashMap::from([("ab", 2), ("cde", 6)]));
+}
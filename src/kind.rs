@@ -0,0 +1,110 @@
+//! Higher kinded types, emulated with witness types.
+//!
+//! A `Kind` is a marker type standing for a type constructor, e.g.,
+//! `OptionK` for `Option`, with `OptionK::Of<T> = Option<T>`.  Generic code
+//! can then take the witness as a type parameter, rather than threading
+//! `TypeMap` bounds and `Coherent` casts through every signature:
+//!
+//! ```
+//! # use functor::kind::{FunctorK, OptionK, VecK};
+//! fn double<F: FunctorK>(x: F::Of<i32>) -> F::Of<i32> {
+//!     F::fmap(x, |x| x * 2)
+//! }
+//! assert_eq!(double::<OptionK>(Some(2)), Some(4));
+//! assert_eq!(double::<VecK>(vec![1, 2]), [2, 4]);
+//! ```
+//!
+//! The witness cannot be inferred from a value, as `F::Of<T>` could be the same
+//! type for different `F`, so it is given explicitly at the call.
+
+use crate::{BoxedTag, FunctorOnce, Mapped, NonEmpty, TypeMap};
+use crate::bifunctor::Proj0;
+use crate::bimapable::Derived;
+use crate::tree::Tree;
+
+use std::collections::{BTreeMap, HashMap, LinkedList, VecDeque};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+
+
+/// A witness for a type constructor.
+pub trait Kind {
+    /// The tag under which `Of<T>` is a functor.
+    type Tag;
+
+    /// Apply the type constructor to `T`.
+    type Of<T> : TypeMap<T, Self::Tag>;
+}
+
+/// A `Kind` whose values can be mapped.
+///
+/// This is `FunctorOnce`, but with the result type fixed as `Self::Of<U>`.
+pub trait FunctorK : Kind {
+    fn fmap<T, U>(x: Self::Of<T>, f: impl FnMut(T) -> U) -> Self::Of<U>;
+}
+
+/// Implement `Kind` and `FunctorK` for the witness `$k`, with `Of<T> = $of`,
+/// mapping through `FunctorOnce` with `$tag`.
+macro_rules! kind {
+    (impl[$($g:tt)*] $k:ty => $of:ty, $tag:ty) => {
+        impl<$($g)*> Kind for $k {
+            type Tag = $tag;
+            type Of<T> = $of;
+        }
+
+        impl<$($g)*> FunctorK for $k {
+            fn fmap<T, U>(x: $of, f: impl FnMut(T) -> U)
+                          -> <Self as Kind>::Of<U> {
+                FunctorOnce::<T, $tag>::fmap_once(x, f)
+            }
+        }
+    };
+}
+
+pub struct OptionK;
+pub struct VecK;
+pub struct VecDequeK;
+pub struct LinkedListK;
+pub struct NonEmptyK;
+pub struct BoxK;
+pub struct TreeK;
+/// `Result` with the error type fixed, mapping the `Ok` value.
+pub struct ResultK<E>(PhantomData<E>);
+/// `BTreeMap` with the key type fixed, mapping the values.
+pub struct MapK<K>(PhantomData<K>);
+/// `HashMap` with the key type and hasher fixed, mapping the values.
+pub struct HashMapK<K, S = RandomState>(PhantomData<(K, S)>);
+/// Arrays of length `N`.
+pub struct ArrayK<const N: usize>;
+
+kind!(impl[] OptionK => Option<T>, ());
+kind!(impl[] VecK => Vec<T>, Mapped);
+kind!(impl[] VecDequeK => VecDeque<T>, Mapped);
+kind!(impl[] LinkedListK => LinkedList<T>, Mapped);
+kind!(impl[] NonEmptyK => NonEmpty<T>, Mapped);
+kind!(impl[] BoxK => Box<T>, BoxedTag);
+kind!(impl[] TreeK => Tree<T>, ());
+kind!(impl[const N: usize] ArrayK<N> => [T; N], ());
+kind!(impl[E] ResultK<E> => Result<T, E>, Proj0<E>);
+kind!(impl[K: Ord] MapK<K> => BTreeMap<K, T>, Derived<K, ()>);
+kind!(impl[K: Eq + Hash, S: BuildHasher + Clone] HashMapK<K, S>
+      => HashMap<K, T, S>, Derived<K, ()>);
+
+
+#[test]
+fn kind_test() {
+    fn labels<F: FunctorK>(x: F::Of<u32>) -> F::Of<String> {
+        F::fmap(F::fmap(x, |n| n + 1), |n| format!("#{n}"))
+    }
+    assert_eq!(labels::<OptionK>(Some(1)).as_deref(), Some("#2"));
+    assert_eq!(labels::<ArrayK<2>>([0, 9]), ["#1", "#10"]);
+    assert_eq!(labels::<ResultK<()>>(Ok(3)), Ok("#4".into()));
+    assert_eq!(*labels::<BoxK>(Box::new(4)), "#5");
+    let m = labels::<MapK<char>>(BTreeMap::from([('a', 1)]));
+    assert_eq!(m, BTreeMap::from([('a', "#2".into())]));
+    let h = labels::<HashMapK<u8>>(HashMap::from([(7, 7)]));
+    assert_eq!(h[&7], "#8");
+    let t = labels::<TreeK>(Tree::new(0, vec![Tree::leaf(1)]));
+    assert_eq!(t.dfs().cloned().collect::<Vec<_>>(), ["#1", "#2"]);
+}
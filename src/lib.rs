@@ -1,22 +1,46 @@
 #![allow(incomplete_features)]
-#![feature(generic_associated_types)]
 #![feature(associated_type_defaults)]
 
 //use std::rc::Rc;
 
+pub mod alternative;
 pub mod applicative;
 pub mod bifunctor;
 pub mod bimapable;
 pub mod boxed;
+pub mod comonad;
+pub mod filterable;
 pub mod functor;
+pub mod graph;
+pub mod indexed;
+pub mod kind;
 pub mod mapable;
+pub mod monad;
+pub mod nonempty;
+pub mod optics;
 pub mod ref_into_iterator;
+pub mod recursion;
 pub mod ref_mapable;
+pub mod these;
+pub mod traverse;
+pub mod tree;
 //pub mod pairmapable;
 
-pub use applicative::{Applicative, ApplicativeOnce};
-pub use bifunctor::{BiCoherent, BiTypeMap, BiFunctor, BiFunctorOnce};
+pub use alternative::{Alternative, MonadPlus};
+pub use applicative::{
+    Applicative, ApplicativeMut, ApplicativeOnce, Apply};
+pub use bifunctor::{
+    BiCoherent, BiTypeMap, BiFunctor, BiFunctorMut, BiFunctorOnce, Bifoldable,
+    Bitraversable};
 pub use boxed::*;
+pub use comonad::{Comonad, Focused};
+pub use filterable::{Filterable, Witherable};
 pub use crate::functor::*;
+pub use indexed::{FunctorWithIndex, FunctorWithKey};
+pub use kind::{FunctorK, Kind};
 pub use mapable::*;
+pub use monad::MonadOnce;
+pub use nonempty::NonEmpty;
 pub use ref_into_iterator::*;
+pub use these::{Align, These};
+pub use traverse::{Sequence, Traverse};
@@ -4,13 +4,18 @@
 //! The Mapable trait does the gory glue, it's implementations just need to
 //! specify the set of types to use.
 
-use crate::{ApplicativeOnce, FunctorOnce, Functor, FunctorMut, RefIntoIterator,
-            TypeMap};
+use crate::{ApplicativeMut, ApplicativeOnce, Apply, Coherent, FunctorOnce, Functor,
+            FunctorMut, FunctorMutate, MonadOnce, RefIntoIterator, TypeMap};
 use std::iter::FromIterator;
 
 use std::collections::{LinkedList, VecDeque};
 
 /// Trait for types which are mapable via iterators.
+///
+/// `FromIterator` need not accept every length, e.g., `NonEmpty` panics on an
+/// empty iterator.  The instances here only build results at least as long as
+/// their inputs, and operations that can shrink a collection, such as
+/// filtering, must not be blanket implemented over `Mapable`.
 pub trait Mapable<T> : FromIterator<T> + IntoIterator<Item = T> where
 {
     // Self::Collection<U>::Collection<V> = Self::Collection<V>
@@ -52,34 +57,106 @@ impl<'a, T: 'a, C: 'a + Mapable<T>> FunctorMut<'a, T, Mapped> for C
     }
 }
 
-impl<T, C: Mapable<T>> ApplicativeOnce<T, Mapped> for C
+/// Mapables that iterate mutably are updated in place.
+impl<T, C: Mapable<T>> FunctorMutate<T, Mapped> for C
+    where for<'a> &'a mut C: IntoIterator<Item = &'a mut T>
 {
-    fn pure_once(x: T) -> C {
-        std::iter::once(x).collect()
+    fn fmutate(&mut self, f: impl FnMut(&mut T)) {
+        self.into_iter().for_each(f)
+    }
+}
+
+/// The cartesian product, in the order of `a` and then `b`.
+///
+/// `b` is collected once, and its elements borrowed by `f` for each element
+/// of `a`.  The result is collected straight from the pairs, whose size hint
+/// counts all of them, so a `Vec` or `VecDeque` is sized up front.
+fn product_with<T, U, V, R: FromIterator<V>>(
+    a: impl IntoIterator<Item = T>, b: impl IntoIterator<Item = U>,
+    f: impl Fn(&T, &U) -> V) -> R
+{
+    let b = b.into_iter().collect();
+    Product { a: a.into_iter(), b, x: None, j: 0, f }.collect()
+}
+
+/// The iterator for `product_with`, at `b[j]` for the element `x` of `a`.
+struct Product<I: Iterator, U, F> {
+    a: I,
+    b: Vec<U>,
+    x: Option<I::Item>,
+    j: usize,
+    f: F,
+}
+
+impl<I: Iterator, U, V, F: Fn(&I::Item, &U) -> V> Iterator
+    for Product<I, U, F>
+{
+    type Item = V;
+
+    fn next(&mut self) -> Option<V> {
+        loop {
+            if let (Some(x), Some(y)) = (&self.x, self.b.get(self.j)) {
+                self.j += 1;
+                return Some((self.f)(x, y));
+            }
+            self.x = Some(self.a.next()?);
+            self.j = 0;
+        }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.b.len();
+        let rest = if self.x.is_some() { n - self.j } else { 0 };
+        let (lo, hi) = self.a.size_hint();
+        (lo.saturating_mul(n).saturating_add(rest),
+         hi.and_then(|h| h.checked_mul(n)?.checked_add(rest)))
+    }
+}
+
+/// The applicative is the cartesian product, as for the list monad.
+impl<T, C: Mapable<T>> Apply<T, Mapped> for C
+{
     fn lift2_once<U, V>(f: impl Fn(T, U) -> V,
                         a: C, b: C::Collection<U>) -> C::Collection<V>
-        where T: Clone, C::Collection<U>: Clone
+        where T: Clone, U: Clone
     {
-        let f = &f;
-        a   .into_iter()
-            .flat_map(|x| b.clone().into_iter().map(move |y| f(x.clone(), y)))
-            .collect()
+        product_with(a, b, |x, y| f(x.clone(), y.clone()))
     }
     fn call_once<A, B>(self, x: C::Collection<A>) -> C::Collection<B>
-        where C::Collection<A>: Clone, T: Fn(A) -> B
+        where A: Clone, T: Fn(A) -> B
     {
-        self.into_iter()
-            .flat_map(|f| x.clone().into_iter().map(f))
-            .collect()
+        product_with(self, x, |f, x| f(x.clone()))
     }
     fn apply_once<U, F: Fn(T) -> U>(self, f: C::Collection<F>)
                                     -> Self::Functor<U>
-        where T: Clone, C::Collection<F>: Clone
+        where T: Clone
     {
-        self.into_iter()
-            .flat_map(|x| f.clone().into_iter().map(move |g| g(x.clone())))
-            .collect()
+        product_with(self, f, |x, g| g(x.clone()))
+    }
+}
+
+impl<T, C: Mapable<T>> ApplicativeOnce<T, Mapped> for C
+{
+    fn pure_once(x: T) -> C {
+        std::iter::once(x).collect()
+    }
+}
+
+impl<T, C: Mapable<T>> MonadOnce<T, Mapped> for C
+{
+    fn bind_once<U>(self, f: impl FnMut(T) -> C::Collection<U>)
+                    -> C::Collection<U> {
+        self.into_iter().flat_map(f).collect()
+    }
+}
+
+/// Every Mapable is coherent, by collecting into the expected type.  For any
+/// sensible instance this is the identity, and for `Vec` collecting reuses the
+/// allocation.
+impl<T, C: Mapable<T, Collection<T> = C>> Coherent<T, Mapped> for C {
+    fn cohere<U, V>(x: <C::Collection<U> as Mapable<U>>::Collection<V>)
+                    -> C::Collection<V> {
+        x.into_iter().collect()
     }
 }
 
@@ -87,9 +164,136 @@ impl<T> Mapable<T> for Vec<T>        { type Collection<U> = Vec<U>; }
 impl<T> Mapable<T> for LinkedList<T> { type Collection<U> = LinkedList<U>; }
 impl<T> Mapable<T> for VecDeque<T>   { type Collection<U> = VecDeque<U>; }
 
+/// Apply every function to every element, in the same order as
+/// `Applicative::apply`.
+///
+/// This cannot be done generically over Mapable, as we need to iterate
+/// mutably over the collection of functions, at every function type.
+fn mut_apply_all<'b, T: 'b, U, F: FnMut(&mut T) -> U, R: FromIterator<U>>(
+    xs: impl IntoIterator<Item = &'b mut T>, fs: &mut impl MutIter<F>) -> R
+{
+    let mut out = Vec::new();
+    for x in xs {
+        fs.each_mut(|g| out.push(g(x)));
+    }
+    out.into_iter().collect()
+}
+
+/// Mutable iteration, for `mut_apply_all`.
+trait MutIter<F> {
+    fn each_mut(&mut self, f: impl FnMut(&mut F));
+}
+
+impl<F> MutIter<F> for Vec<F> {
+    fn each_mut(&mut self, f: impl FnMut(&mut F)) { self.iter_mut().for_each(f) }
+}
+impl<F> MutIter<F> for LinkedList<F> {
+    fn each_mut(&mut self, f: impl FnMut(&mut F)) { self.iter_mut().for_each(f) }
+}
+impl<F> MutIter<F> for VecDeque<F> {
+    fn each_mut(&mut self, f: impl FnMut(&mut F)) { self.iter_mut().for_each(f) }
+}
+
+impl<'a, T: 'a + Clone> ApplicativeMut<'a, T, Mapped> for Vec<T> {
+    fn mut_pure(x: &T) -> Vec<T> { vec![x.clone()] }
+    fn mut_apply<U>(&mut self, f: &mut Vec<impl FnMut(&mut T) -> U>) -> Vec<U> {
+        mut_apply_all(self, f)
+    }
+}
+
+impl<'a, T: 'a + Clone> ApplicativeMut<'a, T, Mapped> for LinkedList<T> {
+    fn mut_pure(x: &T) -> LinkedList<T> { Self::pure_once(x.clone()) }
+    fn mut_apply<U>(&mut self, f: &mut LinkedList<impl FnMut(&mut T) -> U>)
+                    -> LinkedList<U> {
+        mut_apply_all(self, f)
+    }
+}
+
+impl<'a, T: 'a + Clone> ApplicativeMut<'a, T, Mapped> for VecDeque<T> {
+    fn mut_pure(x: &T) -> VecDeque<T> { Self::pure_once(x.clone()) }
+    fn mut_apply<U>(&mut self, f: &mut VecDeque<impl FnMut(&mut T) -> U>)
+                    -> VecDeque<U> {
+        mut_apply_all(self, f)
+    }
+}
+
 #[test]
 fn vec1() {
     let v = vec![1, 2, 3];
     let v2 = v.fmap(|x| x + 4);
     assert_eq!(v2, [5, 6, 7]);
 }
+
+#[test]
+fn apply_mut_vec() {
+    fn inc(x: &mut i32) -> i32 { *x += 1; *x }
+    fn ten(x: &mut i32) -> i32 { *x * 10 }
+    let mut v = vec![1, 2];
+    let mut f: Vec<fn(&mut i32) -> i32> = vec![inc, ten];
+    assert_eq!(v.mut_apply(&mut f), [2, 20, 3, 30]);
+    assert_eq!(v, [2, 3]);
+
+    let mut d: VecDeque<u8> = [1].into_iter().collect();
+    let mut g: VecDeque<_> = [|x: &mut u8| { *x *= 2; *x }].into_iter().collect();
+    assert_eq!(d.mut_apply(&mut g), [2]);
+}
+
+#[test]
+fn cohere_vec() {
+    let v = vec![1, 2, 3].fmap_once(|x| x * 2).fmap_once(|x| x + 1);
+    let v = <Vec<i32> as Coherent<i32, Mapped>>::cohere::<i32, i32>(v);
+    assert_eq!(v, [3, 5, 7]);
+}
+
+#[test]
+fn product_large() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let a: Vec<u32> = (0..1000).collect();
+    let v = Vec::lift2_once(|x, y| x * 1000 + y, a.clone(), a.clone());
+    assert_eq!(v.len(), 1_000_000);
+    assert!(v.iter().enumerate().all(|(i, &x)| x as usize == i));
+    let d = VecDeque::lift2_once(|x, y| (x, y), VecDeque::from([1, 2]),
+                                 VecDeque::from(['a', 'b', 'c']));
+    assert_eq!(d, [(1, 'a'), (1, 'b'), (1, 'c'), (2, 'a'), (2, 'b'), (2, 'c')]);
+    assert!(Vec::lift2_once(|x: u8, y: u8| x + y, vec![1, 2], vec![]).is_empty());
+
+    // The elements of `b` are cloned once per pair, and nothing else.
+    #[derive(Debug)]
+    struct Counted(Rc<Cell<usize>>);
+    impl Clone for Counted {
+        fn clone(&self) -> Counted {
+            self.0.set(self.0.get() + 1);
+            Counted(self.0.clone())
+        }
+    }
+    let n = Rc::new(Cell::new(0));
+    let b: LinkedList<_> = (0..500).map(|_| Counted(n.clone())).collect();
+    let p = LinkedList::lift2_once(|x, _| x, (0..400).collect(), b);
+    assert_eq!((p.len(), n.get()), (200_000, 200_000));
+
+    // Functions are called by reference, so need not be `Clone`.
+    let fs: Vec<Box<dyn Fn(u32) -> u32>> =
+        vec![Box::new(|x| x + 1), Box::new(|x| x * 2)];
+    let r = Apply::call_once(fs, a);
+    assert_eq!((r.len(), r[999], r[1999]), (2000, 1000, 1998));
+}
+
+#[test]
+fn fmutate_in_place() {
+    let mut v: Vec<u64> = (0..100_000).collect();
+    let (ptr, cap) = (v.as_ptr(), v.capacity());
+    for _ in 0..10 {
+        v.fmutate(|x| *x += 1);
+    }
+    assert_eq!((v.as_ptr(), v.capacity()), (ptr, cap));
+    assert_eq!((v[0], v[99_999]), (10, 100_009));
+
+    let mut d = VecDeque::from([1, 2]);
+    d.fmutate(|x| *x = -*x);
+    assert_eq!(d, [-1, -2]);
+    let mut l = LinkedList::from(["a".to_string()]);
+    l.fmutate(|s| s.push('!'));
+    assert_eq!(l.front().map(String::as_str), Some("a!"));
+}
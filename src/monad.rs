@@ -0,0 +1,171 @@
+//! Monads, and do-notation.
+//!
+//! [`mdo!`] desugars a sequence of bindings into nested `bind_once` calls, and
+//! [`ado!`] desugars independent bindings into `lift2_once` calls, so only
+//! needs an applicative.
+
+use crate::{ApplicativeOnce, Comp1};
+use crate::bifunctor::Proj0;
+
+use std::ops::Add;
+
+
+pub trait MonadOnce<T, Tag = ()> : ApplicativeOnce<T, Tag> {
+    /// F(T) × (T → F(U)) → F(U)
+    fn bind_once<U>(self, f: impl FnMut(T) -> Self::Functor<U>)
+                    -> Self::Functor<U>;
+}
+
+impl<T> MonadOnce<T> for Option<T> {
+    fn bind_once<U>(self, f: impl FnMut(T) -> Option<U>) -> Option<U> {
+        self.and_then(f)
+    }
+}
+
+impl<T, E> MonadOnce<T, Proj0<E>> for Result<T, E> {
+    fn bind_once<U>(self, f: impl FnMut(T) -> Result<U, E>) -> Result<U, E> {
+        self.and_then(f)
+    }
+}
+
+/// The writer monad, accumulating `.0` with `+`.
+impl<A: Default + Add<Output = A>, T> MonadOnce<T, Comp1> for (A, T) {
+    fn bind_once<U>(self, mut f: impl FnMut(T) -> (A, U)) -> (A, U) {
+        let (a, u) = f(self.1);
+        (self.0 + a, u)
+    }
+}
+
+
+/// Monadic do-notation.
+///
+/// Each statement is one of:
+/// * `pat <- expr;` binds the value(s) in `expr`, where `pat` is an
+///   identifier, `mut` identifier, `_` or a tuple pattern.
+/// * `let pat = expr;` is an ordinary `let`.
+/// * `guard cond;` stops if `cond` is false, giving `Alternative::empty()`.
+/// * `expr;` runs `expr` and discards the value(s).
+///
+/// The last item is either `pure(expr)` or an expression giving the monad.
+///
+/// ```
+/// # use functor::mdo;
+/// let r = mdo! { x <- Some(1); let y = x + 1; z <- Some(y * 10); pure(x + z) };
+/// assert_eq!(r, Some(21));
+/// ```
+#[macro_export]
+macro_rules! mdo {
+    (pure($e:expr) $(;)?) => {
+        $crate::ApplicativeOnce::pure_once($e)
+    };
+    (let $p:pat = $e:expr; $($rest:tt)+) => {
+        { let $p = $e; $crate::mdo!($($rest)+) }
+    };
+    (guard $c:expr; $($rest:tt)+) => {
+        if $c { $crate::mdo!($($rest)+) }
+        else { $crate::alternative::Alternative::empty() }
+    };
+    (mut $p:ident <- $e:expr; $($rest:tt)+) => {
+        $crate::monad::MonadOnce::bind_once($e, |mut $p| $crate::mdo!($($rest)+))
+    };
+    ($p:tt <- $e:expr; $($rest:tt)+) => {
+        $crate::monad::MonadOnce::bind_once($e, |$p| $crate::mdo!($($rest)+))
+    };
+    ($(mut)? $p:tt <- $e:expr $(;)?) => {
+        ::std::compile_error!(
+            "the last item in `mdo!` must be an expression, not a binding")
+    };
+    ($e:expr; $($rest:tt)+) => {
+        $crate::monad::MonadOnce::bind_once($e, |_| $crate::mdo!($($rest)+))
+    };
+    ($e:expr) => { $e };
+}
+
+/// Applicative do-notation.
+///
+/// This is a sequence of bindings `pat <- expr;`, ending with `pure(expr)`.
+/// The bound expressions cannot refer to earlier bindings, so this only needs
+/// `ApplicativeOnce`.  The bound values must be `Clone`.
+///
+/// ```
+/// # use functor::ado;
+/// let r = ado! { x <- Some(1); y <- Some(2); pure(x + y) };
+/// assert_eq!(r, Some(3));
+/// ```
+#[macro_export]
+macro_rules! ado {
+    (pure($e:expr) $(;)?) => {
+        $crate::ApplicativeOnce::pure_once($e)
+    };
+    ($p:tt <- $e:expr; $($rest:tt)+) => {
+        $crate::ado!(@bind [$p] [$e] $($rest)+)
+    };
+    (@bind [$($p:tt)*] [$($e:expr),*] $q:tt <- $f:expr; $($rest:tt)+) => {
+        $crate::ado!(@bind [$($p)* $q] [$($e,)* $f] $($rest)+)
+    };
+    (@bind [$p:tt $($ps:tt)*] [$e:expr $(, $es:expr)*] pure($r:expr) $(;)?) => {
+        $crate::Apply::lift2_once(
+            |$p, $crate::ado!(@pat $($ps)*)| $r,
+            $e, $crate::ado!(@nest $($es),*))
+    };
+    (@bind [$($p:tt)*] [$($e:expr),*] $($rest:tt)*) => {
+        ::std::compile_error!(
+            "`ado!` takes bindings `pat <- expr;` followed by `pure(expr)`")
+    };
+    (@pat) => { () };
+    (@pat $p:tt $($ps:tt)*) => { ($p, $crate::ado!(@pat $($ps)*)) };
+    (@nest) => { $crate::ApplicativeOnce::pure_once(()) };
+    (@nest $e:expr $(, $es:expr)*) => {
+        $crate::Apply::lift2_once(
+            |x, y| (x, y), $e, $crate::ado!(@nest $($es),*))
+    };
+    ($($t:tt)*) => {
+        ::std::compile_error!(
+            "`ado!` takes bindings `pat <- expr;` followed by `pure(expr)`")
+    };
+}
+
+
+#[test]
+fn mdo_option_result() {
+    let half = |x: u32| if x.is_multiple_of(2) { Some(x / 2) } else { None };
+    let r = mdo! { a <- half(20); b <- half(a); pure(a + b) };
+    assert_eq!(r, Some(15));
+    assert_eq!(mdo! { a <- half(20); b <- half(a); half(b) }, None);
+
+    let parse = |s: &str| s.parse::<i32>().map_err(|_| s.to_string());
+    let r: Result<i32, String> = mdo! {
+        x <- parse("3");
+        let y = x * 2;
+        z <- parse("nope");
+        pure(y + z)
+    };
+    assert_eq!(r, Err("nope".to_string()));
+}
+
+#[test]
+fn mdo_vec_guard() {
+    let r: Vec<(u32, u32, u32)> = mdo! {
+        a <- (1..=13).collect::<Vec<_>>();
+        b <- (a..=13).collect::<Vec<_>>();
+        c <- (b..=13).collect::<Vec<_>>();
+        guard a * a + b * b == c * c;
+        pure((a, b, c))
+    };
+    assert_eq!(r, [(3, 4, 5), (5, 12, 13), (6, 8, 10)]);
+
+    let w: (u32, &str) = mdo! { (1, ()); x <- (2, "a"); pure(x) };
+    assert_eq!(w, (3, "a"));
+}
+
+#[test]
+fn ado_test() {
+    let r = ado! { x <- Some(1); y <- Some(2); z <- Some(3); pure(x + y * z) };
+    assert_eq!(r, Some(7));
+    let n: Option<i32> = ado! { x <- Some(1); y <- None::<i32>; pure(x + y) };
+    assert_eq!(n, None);
+
+    let v = ado! { x <- vec![1, 2]; y <- vec![10, 20]; pure(x * y) };
+    assert_eq!(v, [10, 20, 20, 40]);
+    assert_eq!(ado! { x <- Ok::<_, ()>(4); pure(x) }, Ok(4));
+}
@@ -0,0 +1,168 @@
+//! A vector with at least one element.
+//!
+//! This is a Mapable and RefMapable, so gets Functor and Applicative from the
+//! generic implementations.  Mapping preserves length, and the applicative
+//! product of non-empty collections is non-empty, so the non-emptiness is
+//! preserved.
+
+use crate::Mapable;
+use crate::ref_mapable::RefMapable;
+
+use std::iter::{Chain, Once, once};
+
+
+/// A non-empty vector, stored as the first element and the rest.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NonEmpty<T> {
+    pub head: T,
+    pub tail: Vec<T>,
+}
+
+impl<T> NonEmpty<T> {
+    /// A single element.
+    pub fn new(head: T) -> NonEmpty<T> { NonEmpty { head, tail: Vec::new() } }
+
+    /// `None` if `v` is empty.
+    pub fn from_vec(v: Vec<T>) -> Option<NonEmpty<T>> {
+        let mut iter = v.into_iter();
+        let head = iter.next()?;
+        Some(NonEmpty { head, tail: iter.collect() })
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        let mut v = Vec::with_capacity(self.len());
+        v.push(self.head);
+        v.extend(self.tail);
+        v
+    }
+
+    pub fn len(&self) -> usize { self.tail.len() + 1 }
+
+    /// Always false, but here for consistency with other collections.
+    pub fn is_empty(&self) -> bool { false }
+
+    pub fn push(&mut self, x: T) { self.tail.push(x) }
+
+    /// Move all the elements of `other` onto the end of `self`.
+    pub fn append(&mut self, other: NonEmpty<T>) {
+        self.tail.reserve(other.len());
+        self.tail.push(other.head);
+        self.tail.extend(other.tail);
+    }
+
+    /// Split into the first element and the rest.
+    pub fn into_head(self) -> (T, Vec<T>) { (self.head, self.tail) }
+
+    pub fn first(&self) -> &T { &self.head }
+
+    pub fn last(&self) -> &T { self.tail.last().unwrap_or(&self.head) }
+
+    /// The maximum element, the last if several are equal.
+    pub fn max(&self) -> &T where T: Ord {
+        self.tail.iter().fold(&self.head, |m, x| if x >= m { x } else { m })
+    }
+
+    /// The minimum element, the first if several are equal.
+    pub fn min(&self) -> &T where T: Ord {
+        self.tail.iter().fold(&self.head, |m, x| if x < m { x } else { m })
+    }
+
+    pub fn iter(&self) -> <&NonEmpty<T> as IntoIterator>::IntoIter {
+        self.into_iter()
+    }
+
+    pub fn iter_mut(&mut self) -> <&mut NonEmpty<T> as IntoIterator>::IntoIter {
+        self.into_iter()
+    }
+}
+
+impl<T> From<NonEmpty<T>> for Vec<T> {
+    fn from(x: NonEmpty<T>) -> Vec<T> { x.into_vec() }
+}
+
+/// Fails, returning the vector, if it is empty.
+impl<T> TryFrom<Vec<T>> for NonEmpty<T> {
+    type Error = Vec<T>;
+    fn try_from(v: Vec<T>) -> Result<NonEmpty<T>, Vec<T>> {
+        if v.is_empty() { Err(v) } else { Ok(NonEmpty::from_vec(v).unwrap()) }
+    }
+}
+
+impl<T> IntoIterator for NonEmpty<T> {
+    type Item = T;
+    type IntoIter = Chain<Once<T>, std::vec::IntoIter<T>>;
+    fn into_iter(self) -> Self::IntoIter {
+        once(self.head).chain(self.tail)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a NonEmpty<T> {
+    type Item = &'a T;
+    type IntoIter = Chain<Once<&'a T>, std::slice::Iter<'a, T>>;
+    fn into_iter(self) -> Self::IntoIter {
+        once(&self.head).chain(&self.tail)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut NonEmpty<T> {
+    type Item = &'a mut T;
+    type IntoIter = Chain<Once<&'a mut T>, std::slice::IterMut<'a, T>>;
+    fn into_iter(self) -> Self::IntoIter {
+        once(&mut self.head).chain(&mut self.tail)
+    }
+}
+
+/// Needed for Mapable.  Panics if the iterator is empty.  The generic functor,
+/// applicative and monad instances never shrink a collection, but anything
+/// that can, such as filtering, must not be implemented over all `Mapable`s.
+impl<T> FromIterator<T> for NonEmpty<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> NonEmpty<T> {
+        let mut iter = iter.into_iter();
+        let head = iter.next().expect("NonEmpty from an empty iterator");
+        NonEmpty { head, tail: iter.collect() }
+    }
+}
+
+impl<T> Extend<T> for NonEmpty<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.tail.extend(iter)
+    }
+}
+
+impl<T> Mapable<T> for NonEmpty<T> { type Collection<U> = NonEmpty<U>; }
+
+impl<'a, T: 'a> RefMapable<'a, T> for NonEmpty<T> {
+    type RefColl<'b, U: 'b> = NonEmpty<U>;
+    fn inject<'b, U: 'b>(x : &'b NonEmpty<U>) -> &'b NonEmpty<U> { x }
+}
+
+
+#[test]
+fn nonempty_test() {
+    use crate::{Applicative, Apply, Functor, FunctorMut, FunctorOnce};
+
+    let mut n = NonEmpty::from_vec(vec![3, 1, 4]).unwrap();
+    assert_eq!(NonEmpty::<u8>::from_vec(vec![]), None);
+    assert_eq!((*n.first(), *n.last(), *n.max(), *n.min()), (3, 4, 4, 1));
+
+    assert_eq!(n.fmap(|x| x * 2).into_vec(), [6, 2, 8]);
+    let sq = n.fmap_mut(|x| { *x += 1; *x * *x });
+    assert_eq!(Vec::from(sq), [16, 4, 25]);
+    assert_eq!(n.clone().fmap_once(|x| x.to_string()).head, "4");
+
+    n.append(NonEmpty::new(9));
+    assert_eq!(n.len(), 4);
+    assert_eq!(n.clone().into_head(), (4, vec![2, 5, 9]));
+
+    let p = NonEmpty::lift2_once(|x, y: u32| x * y, NonEmpty::new(2),
+                                 NonEmpty::from_vec(vec![1, 10]).unwrap());
+    assert_eq!(p.into_vec(), [2, 20]);
+    let q = NonEmpty::lift2(|x, y| x + y, &n, &NonEmpty::pure(&100));
+    assert_eq!(q.into_vec(), [104, 102, 105, 109]);
+}
+
+#[test]
+#[should_panic(expected = "NonEmpty from an empty iterator")]
+fn collect_empty() {
+    let _: NonEmpty<u8> = std::iter::empty().collect();
+}
@@ -0,0 +1,493 @@
+//! Van Laarhoven style optics.
+//!
+//! An optic over a structure `S` with focus `A` is, in the van Laarhoven
+//! encoding, a function `(A → F(A)) → S → F(S)` that works for every functor
+//! (lens) or every applicative (traversal) `F`.  Rust has no rank-2 types, so
+//! the quantification over `F` is a generic method on a trait, and `F` ranges
+//! over a type-level family rather than a type.
+//!
+//! All the user-visible operations (`view`, `set`, `over`, `to_list_of`,
+//! `preview`) are obtained by running an optic at one of two internal
+//! functors: [`Identity`] (for modification) and [`Const`] (for extraction).
+//!
+//! These are `FunctorOnce` like any other functor in the crate, and the
+//! optics map through those impls.  `FunctorOnce` alone does not tell generic
+//! code that mapping `Of<T>` gives back `Of<U>`, though, so optics are run at
+//! a family, [`OpticFunctor`], which fixes the type constructor.
+//!
+//! Optics compose with `.then()`; the composite is the weakest of the two
+//! kinds, e.g., a lens then a traversal is a traversal.
+
+use crate::{FunctorOnce, TypeMap};
+
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+/// The identity functor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Identity<T>(pub T);
+
+impl<T> TypeMap<T> for Identity<T> { type Functor<U> = Identity<U>; }
+
+impl<T> FunctorOnce<T> for Identity<T> {
+    fn fmap_once<U>(self, mut f: impl FnMut(T) -> U) -> Identity<U> {
+        Identity(f(self.0))
+    }
+}
+
+/// The constant functor, carrying an `R` and ignoring `T`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Const<R, T>(pub R, PhantomData<T>);
+
+impl<R, T> Const<R, T> {
+    pub fn new(r: R) -> Const<R, T> { Const(r, PhantomData) }
+}
+
+impl<R, T> TypeMap<T> for Const<R, T> { type Functor<U> = Const<R, U>; }
+
+impl<R, T> FunctorOnce<T> for Const<R, T> {
+    fn fmap_once<U>(self, _: impl FnMut(T) -> U) -> Const<R, U> {
+        Const::new(self.0)
+    }
+}
+
+crate::coherent!(impl[T] Coherent<T> for Identity<T> => Identity<V>);
+crate::coherent!(impl[R, T] Coherent<T> for Const<R, T> => Const<R, V>);
+
+
+/// A functor, given as a type-level family, that optics can be run at.
+///
+/// `Of<T>` is the functor applied to `T`.  Unlike `FunctorOnce`, the family
+/// form lets generic code know that mapping `Of<T>` gives `Of<U>`.
+pub trait OpticFunctor {
+    type Of<T> : FunctorOnce<T, Item = T>;
+    fn fmap<T, U>(x: Self::Of<T>, f: impl FnOnce(T) -> U) -> Self::Of<U>;
+}
+
+/// An applicative family that traversals can be run at.
+pub trait OpticApplicative : OpticFunctor {
+    fn pure<T>(x: T) -> Self::Of<T>;
+    fn lift2<T, U, V>(f: impl FnOnce(T, U) -> V,
+                      a: Self::Of<T>, b: Self::Of<U>) -> Self::Of<V>;
+}
+
+/// Family for [`Identity`].
+pub struct IdentityF;
+
+impl OpticFunctor for IdentityF {
+    type Of<T> = Identity<T>;
+    fn fmap<T, U>(x: Identity<T>, f: impl FnOnce(T) -> U) -> Identity<U> {
+        Identity(f(x.0))
+    }
+}
+
+impl OpticApplicative for IdentityF {
+    fn pure<T>(x: T) -> Identity<T> { Identity(x) }
+    fn lift2<T, U, V>(f: impl FnOnce(T, U) -> V,
+                      a: Identity<T>, b: Identity<U>) -> Identity<V> {
+        Identity(f(a.0, b.0))
+    }
+}
+
+/// Family for [`Const`] at a fixed `R`.
+pub struct ConstF<R>(PhantomData<R>);
+
+impl<R> OpticFunctor for ConstF<R> {
+    type Of<T> = Const<R, T>;
+    fn fmap<T, U>(x: Const<R, T>, _: impl FnOnce(T) -> U) -> Const<R, U> {
+        Const::new(x.0)
+    }
+}
+
+/// Collecting all the foci of a traversal.
+impl<R> OpticApplicative for ConstF<Vec<R>> {
+    fn pure<T>(_: T) -> Const<Vec<R>, T> { Const::new(Vec::new()) }
+    fn lift2<T, U, V>(_: impl FnOnce(T, U) -> V,
+                      a: Const<Vec<R>, T>, b: Const<Vec<R>, U>)
+                      -> Const<Vec<R>, V> {
+        let mut v = a.0;
+        v.extend(b.0);
+        Const::new(v)
+    }
+}
+
+/// Keeping the first focus of a traversal.
+impl<R> OpticApplicative for ConstF<Option<R>> {
+    fn pure<T>(_: T) -> Const<Option<R>, T> { Const::new(None) }
+    fn lift2<T, U, V>(_: impl FnOnce(T, U) -> V,
+                      a: Const<Option<R>, T>, b: Const<Option<R>, U>)
+                      -> Const<Option<R>, V> {
+        Const::new(a.0.or(b.0))
+    }
+}
+
+
+/// Anything that can modify the foci `A` within an `S`.
+pub trait SetterLike<S, A> {
+    /// Apply `f` to every focus.
+    fn over(&self, s: S, f: impl FnMut(A) -> A) -> S;
+
+    /// Replace every focus with `a`.
+    fn set(&self, s: S, a: A) -> S where A: Clone {
+        self.over(s, |_| a.clone())
+    }
+
+    /// Compose with an optic focusing inside `A`.
+    fn then<B, O>(self, other: O) -> Then<Self, O, A>
+        where Self: Sized, O: SetterLike<A, B>
+    {
+        Then(self, other, PhantomData)
+    }
+}
+
+/// An optic that can be run at any applicative: zero or more foci.
+pub trait TraversalLike<S, A> : SetterLike<S, A> {
+    fn traverse_at<F: OpticApplicative>(
+        &self, s: S, f: impl FnMut(A) -> F::Of<A>) -> F::Of<S>;
+
+    /// Collect all the foci in order.
+    fn to_list_of(&self, s: S) -> Vec<A> {
+        self.traverse_at::<ConstF<Vec<A>>>(s, |a| Const::new(vec![a])).0
+    }
+
+    /// The first focus, if any.
+    fn preview(&self, s: S) -> Option<A> {
+        self.traverse_at::<ConstF<Option<A>>>(s, |a| Const::new(Some(a))).0
+    }
+}
+
+/// An optic that can be run at any functor: exactly one focus.
+pub trait LensLike<S, A> : TraversalLike<S, A> {
+    fn lens_at<F: OpticFunctor>(
+        &self, s: S, f: impl FnOnce(A) -> F::Of<A>) -> F::Of<S>;
+
+    /// Extract the focus.
+    fn view(&self, s: S) -> A {
+        self.lens_at::<ConstF<A>>(s, Const::new).0
+    }
+}
+
+/// A traversal with at most one focus that can also build an `S` from an `A`.
+pub trait PrismLike<S, A> : TraversalLike<S, A> {
+    fn review(&self, a: A) -> S;
+}
+
+
+/// A lens given by a getter and a setter.
+pub struct Lens<S, A> {
+    get: Rc<dyn Fn(&S) -> A>,
+    put: Rc<dyn Fn(S, A) -> S>,
+}
+
+impl<S, A> Clone for Lens<S, A> {
+    fn clone(&self) -> Self {
+        Lens { get: self.get.clone(), put: self.put.clone() }
+    }
+}
+
+impl<S, A> Lens<S, A> {
+    pub fn new(get: impl Fn(&S) -> A + 'static,
+               put: impl Fn(S, A) -> S + 'static) -> Lens<S, A> {
+        Lens { get: Rc::new(get), put: Rc::new(put) }
+    }
+}
+
+impl<S, A> SetterLike<S, A> for Lens<S, A> {
+    fn over(&self, s: S, mut f: impl FnMut(A) -> A) -> S {
+        self.lens_at::<IdentityF>(s, |a| Identity(f(a))).0
+    }
+}
+
+impl<S, A> TraversalLike<S, A> for Lens<S, A> {
+    fn traverse_at<F: OpticApplicative>(
+        &self, s: S, f: impl FnMut(A) -> F::Of<A>) -> F::Of<S> {
+        self.lens_at::<F>(s, f)
+    }
+}
+
+impl<S, A> LensLike<S, A> for Lens<S, A> {
+    fn lens_at<F: OpticFunctor>(
+        &self, s: S, f: impl FnOnce(A) -> F::Of<A>) -> F::Of<S> {
+        let a = (self.get)(&s);
+        F::fmap(f(a), move |a| (self.put)(s, a))
+    }
+}
+
+
+type Contents<S, A> = dyn Fn(&S) -> Vec<A>;
+
+/// A traversal given by extracting the foci and putting them back.
+///
+/// `put` receives as many foci as `get` produced, in the same order.
+pub struct Traversal<S, A> {
+    get: Rc<Contents<S, A>>,
+    put: Rc<dyn Fn(S, Vec<A>) -> S>,
+}
+
+impl<S, A> Clone for Traversal<S, A> {
+    fn clone(&self) -> Self {
+        Traversal { get: self.get.clone(), put: self.put.clone() }
+    }
+}
+
+impl<S, A> Traversal<S, A> {
+    pub fn new(get: impl Fn(&S) -> Vec<A> + 'static,
+               put: impl Fn(S, Vec<A>) -> S + 'static) -> Traversal<S, A> {
+        Traversal { get: Rc::new(get), put: Rc::new(put) }
+    }
+
+    /// Traverse every element of a collection.
+    pub fn each() -> Traversal<S, A>
+        where S: 'static + FromIterator<A>, A: 'static + Clone,
+              for<'b> &'b S: IntoIterator<Item = &'b A>
+    {
+        Traversal::new(|s: &S| s.into_iter().cloned().collect(),
+                       |_, v| v.into_iter().collect())
+    }
+}
+
+impl<S, A> SetterLike<S, A> for Traversal<S, A> {
+    fn over(&self, s: S, mut f: impl FnMut(A) -> A) -> S {
+        self.traverse_at::<IdentityF>(s, |a| Identity(f(a))).0
+    }
+}
+
+impl<S, A> TraversalLike<S, A> for Traversal<S, A> {
+    fn traverse_at<F: OpticApplicative>(
+        &self, s: S, mut f: impl FnMut(A) -> F::Of<A>) -> F::Of<S> {
+        let foci = (self.get)(&s);
+        let mut acc = F::pure(Vec::with_capacity(foci.len()));
+        for a in foci {
+            acc = F::lift2(|mut v, b| { v.push(b); v }, acc, f(a));
+        }
+        F::fmap(acc, move |v| (self.put)(s, v))
+    }
+}
+
+
+/// A prism given by a partial match and a constructor.
+pub struct Prism<S, A> {
+    get: Rc<dyn Fn(S) -> Result<A, S>>,
+    put: Rc<dyn Fn(A) -> S>,
+}
+
+impl<S, A> Clone for Prism<S, A> {
+    fn clone(&self) -> Self {
+        Prism { get: self.get.clone(), put: self.put.clone() }
+    }
+}
+
+impl<S, A> Prism<S, A> {
+    /// `get` returns the original on mismatch.
+    pub fn new(get: impl Fn(S) -> Result<A, S> + 'static,
+               put: impl Fn(A) -> S + 'static) -> Prism<S, A> {
+        Prism { get: Rc::new(get), put: Rc::new(put) }
+    }
+}
+
+impl<A: 'static> Prism<Option<A>, A> {
+    /// Focus on the `Some` case.
+    pub fn some() -> Prism<Option<A>, A> {
+        Prism::new(|s: Option<A>| s.ok_or(None), Some)
+    }
+}
+
+impl<A: 'static, E: 'static> Prism<Result<A, E>, A> {
+    /// Focus on the `Ok` case.
+    pub fn ok() -> Prism<Result<A, E>, A> {
+        Prism::new(|s: Result<A, E>| s.map_err(Err), Ok)
+    }
+}
+
+impl<S, A> SetterLike<S, A> for Prism<S, A> {
+    fn over(&self, s: S, mut f: impl FnMut(A) -> A) -> S {
+        self.traverse_at::<IdentityF>(s, |a| Identity(f(a))).0
+    }
+}
+
+impl<S, A> TraversalLike<S, A> for Prism<S, A> {
+    fn traverse_at<F: OpticApplicative>(
+        &self, s: S, mut f: impl FnMut(A) -> F::Of<A>) -> F::Of<S> {
+        match (self.get)(s) {
+            Ok(a) => F::fmap(f(a), |a| (self.put)(a)),
+            Err(s) => F::pure(s),
+        }
+    }
+}
+
+impl<S, A> PrismLike<S, A> for Prism<S, A> {
+    fn review(&self, a: A) -> S { (self.put)(a) }
+}
+
+
+type Over<S, A> = dyn Fn(S, &mut dyn FnMut(A) -> A) -> S;
+
+/// A setter: modification only.
+pub struct Setter<S, A> {
+    over: Rc<Over<S, A>>,
+}
+
+impl<S, A> Clone for Setter<S, A> {
+    fn clone(&self) -> Self { Setter { over: self.over.clone() } }
+}
+
+impl<S, A> Setter<S, A> {
+    pub fn new(over: impl Fn(S, &mut dyn FnMut(A) -> A) -> S + 'static)
+               -> Setter<S, A> {
+        Setter { over: Rc::new(over) }
+    }
+
+    /// Modify everything a functor maps over.
+    pub fn mapped<Tag>() -> Setter<S, A>
+        where S: 'static + FunctorOnce<A, Tag, Item = A, Functor<A> = S>,
+              A: 'static, Tag: 'static
+    {
+        Setter::new(|s: S, f| s.fmap_once(f))
+    }
+}
+
+impl<S, A> SetterLike<S, A> for Setter<S, A> {
+    fn over(&self, s: S, mut f: impl FnMut(A) -> A) -> S {
+        (self.over)(s, &mut f)
+    }
+}
+
+
+/// Composition of two optics, focusing through the intermediate `B`.
+pub struct Then<O1, O2, B>(O1, O2, PhantomData<fn(B) -> B>);
+
+impl<O1: Clone, O2: Clone, B> Clone for Then<O1, O2, B> {
+    fn clone(&self) -> Self { Then(self.0.clone(), self.1.clone(), PhantomData) }
+}
+
+impl<S, B, A, O1, O2> SetterLike<S, A> for Then<O1, O2, B>
+    where O1: SetterLike<S, B>, O2: SetterLike<B, A>
+{
+    fn over(&self, s: S, mut f: impl FnMut(A) -> A) -> S {
+        self.0.over(s, |b| self.1.over(b, &mut f))
+    }
+}
+
+impl<S, B, A, O1, O2> TraversalLike<S, A> for Then<O1, O2, B>
+    where O1: TraversalLike<S, B>, O2: TraversalLike<B, A>
+{
+    fn traverse_at<F: OpticApplicative>(
+        &self, s: S, mut f: impl FnMut(A) -> F::Of<A>) -> F::Of<S> {
+        self.0.traverse_at::<F>(s, |b| self.1.traverse_at::<F>(b, &mut f))
+    }
+}
+
+impl<S, B, A, O1, O2> LensLike<S, A> for Then<O1, O2, B>
+    where O1: LensLike<S, B>, O2: LensLike<B, A>
+{
+    fn lens_at<F: OpticFunctor>(
+        &self, s: S, f: impl FnOnce(A) -> F::Of<A>) -> F::Of<S> {
+        self.0.lens_at::<F>(s, |b| self.1.lens_at::<F>(b, f))
+    }
+}
+
+impl<S, B, A, O1, O2> PrismLike<S, A> for Then<O1, O2, B>
+    where O1: PrismLike<S, B>, O2: PrismLike<B, A>
+{
+    fn review(&self, a: A) -> S { self.0.review(self.1.review(a)) }
+}
+
+
+/// A lens onto a field of a struct, e.g., `field_lens!(Point, x)`.  The field
+/// type must be `Clone`.
+#[macro_export]
+macro_rules! field_lens {
+    ($s:ty, $field:tt) => {
+        $crate::optics::Lens::new(
+            |s: &$s| ::std::clone::Clone::clone(&s.$field),
+            |mut s: $s, a| { s.$field = a; s })
+    };
+}
+
+/// Derive lens constructors for the fields of a struct:
+///
+/// ```
+/// # use functor::{lenses, optics::LensLike};
+/// #[derive(Clone)]
+/// struct Point { x: i32, y: i32 }
+/// lenses! { Point { pub fn x_lens() => x: i32; fn y_lens() => y: i32; } }
+/// assert_eq!(Point::x_lens().view(Point { x: 1, y: 2 }), 1);
+/// ```
+#[macro_export]
+macro_rules! lenses {
+    ($s:ty { $($vis:vis fn $name:ident() => $field:tt : $t:ty;)* }) => {
+        impl $s {
+            $(
+                $vis fn $name() -> $crate::optics::Lens<$s, $t> {
+                    $crate::field_lens!($s, $field)
+                }
+            )*
+        }
+    };
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Point { x: i32, y: i32 }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Shape { name: &'static str, points: Vec<Point>, centre: Option<Point> }
+
+    lenses! {
+        Point { fn x_lens() => x: i32; fn y_lens() => y: i32; }
+    }
+    lenses! {
+        Shape { fn points() => points: Vec<Point>;
+                fn centre() => centre: Option<Point>; }
+    }
+
+    #[test]
+    fn lens_view_set_over() {
+        let p = Point { x: 1, y: 2 };
+        assert_eq!(Point::x_lens().view(p.clone()), 1);
+        assert_eq!(Point::y_lens().set(p.clone(), 5), Point { x: 1, y: 5 });
+        assert_eq!(field_lens!(Point, x).over(p, |x| x * 10),
+                   Point { x: 10, y: 2 });
+
+        let pair = (1u8, "a");
+        assert_eq!(field_lens!((u8, &str), 1).view(pair), "a");
+    }
+
+    #[test]
+    fn traversal_compose() {
+        let s = Shape { name: "tri",
+                        points: vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }],
+                        centre: None };
+        let xs = Shape::points().then(Traversal::each()).then(Point::x_lens());
+        assert_eq!(xs.to_list_of(s.clone()), [1, 3]);
+        assert_eq!(xs.preview(s.clone()), Some(1));
+
+        let s2 = xs.over(s.clone(), |x| -x);
+        assert_eq!(s2.points, [Point { x: -1, y: 2 }, Point { x: -3, y: 4 }]);
+        assert_eq!(s2.name, "tri");
+
+        let ys = Shape::points().then(Setter::mapped()).then(Point::y_lens());
+        assert_eq!(ys.set(s, 0).points,
+                   [Point { x: 1, y: 0 }, Point { x: 3, y: 0 }]);
+    }
+
+    #[test]
+    fn prism_compose() {
+        let cx = Shape::centre().then(Prism::some()).then(Point::x_lens());
+        let s = Shape { name: "dot", points: vec![], centre: None };
+        assert_eq!(cx.preview(s.clone()), None);
+        assert_eq!(cx.over(s.clone(), |x| x + 1), s);
+
+        let s = Shape { centre: Some(Point { x: 7, y: 8 }), ..s };
+        assert_eq!(cx.to_list_of(s.clone()), [7]);
+        assert_eq!(cx.set(s, 0).centre, Some(Point { x: 0, y: 8 }));
+
+        let p = Prism::<Result<Option<u32>, ()>, _>::ok().then(Prism::some());
+        assert_eq!(p.review(3), Ok(Some(3)));
+        assert_eq!(p.preview(Ok(None)), None);
+    }
+}
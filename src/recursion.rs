@@ -0,0 +1,244 @@
+//! Recursion schemes over the fixed point of a pattern functor.
+//!
+//! A recursive type like an expression tree is written as a non-recursive
+//! pattern functor `ExprF<R>`, with `R` in place of the recursive positions.
+//! `Fix<ExprF<()>>` is then the recursive type, and folds and unfolds come
+//! for free from `FunctorOnce`.
+//!
+//! All the schemes use an explicit stack, as does dropping a `Fix`, so deep
+//! structures do not overflow the call stack.
+
+use crate::{Coherent, FunctorOnce, TypeMap};
+
+
+/// One layer of a `Fix<F, Tag>`, with the children as `Fix`.
+pub type Layer<F, Tag = ()> = <F as TypeMap<(), Tag>>::Functor<Fix<F, Tag>>;
+
+type Children<F, Tag> = fn(Layer<F, Tag>, &mut Vec<Fix<F, Tag>>);
+
+/// The fixed point of the functor `F`, given by its instance at `()`.
+pub struct Fix<F: TypeMap<(), Tag>, Tag = ()> {
+    layer: Option<Box<F::Functor<Fix<F, Tag>>>>,
+    /// Moves the children out of a layer, so that `drop` needs no recursion,
+    /// and no bounds beyond those on the struct.
+    children: Children<F, Tag>,
+}
+
+impl<F: TypeMap<(), Tag>, Tag> Fix<F, Tag> {
+    pub fn new(layer: F::Functor<Fix<F, Tag>>) -> Fix<F, Tag>
+        where F::Functor<Fix<F, Tag>>: FunctorOnce<Fix<F, Tag>, Tag,
+                                                   Item = Fix<F, Tag>>
+    {
+        Fix { layer: Some(Box::new(layer)),
+              children: |l, v| { l.fmap_once(|c| v.push(c)); } }
+    }
+
+    /// The top layer.
+    pub fn layer(&self) -> &F::Functor<Fix<F, Tag>> {
+        self.layer.as_ref().unwrap()
+    }
+
+    /// Take apart the top layer.
+    pub fn unfix(mut self) -> F::Functor<Fix<F, Tag>> {
+        *self.layer.take().unwrap()
+    }
+
+    /// Catamorphism: fold bottom up with `alg`.
+    pub fn cata<A>(self, alg: impl FnMut(F::Functor<A>) -> A) -> A
+        where F: Coherent<(), Tag>,
+              F::Functor<Fix<F, Tag>>: FunctorOnce<Fix<F, Tag>, Tag,
+                                                   Item = Fix<F, Tag>>,
+              F::Functor<usize>: FunctorOnce<usize, Tag, Item = usize>,
+    {
+        hylo::<F, Tag, _, _>(self, Fix::unfix, alg)
+    }
+
+    /// Anamorphism: unfold top down from `seed` with `coalg`.
+    pub fn ana<S>(seed: S, coalg: impl FnMut(S) -> F::Functor<S>)
+                  -> Fix<F, Tag>
+        where F: Coherent<(), Tag>,
+              F::Functor<S>: FunctorOnce<S, Tag, Item = S>,
+              F::Functor<Fix<F, Tag>>: FunctorOnce<Fix<F, Tag>, Tag,
+                                                   Item = Fix<F, Tag>>,
+              F::Functor<usize>: FunctorOnce<usize, Tag, Item = usize>,
+    {
+        hylo::<F, Tag, _, _>(seed, coalg, Fix::new)
+    }
+
+    /// Paramorphism: fold bottom up, with `alg` also seeing each original
+    /// subtree alongside its result.
+    pub fn para<A>(
+        self, mut alg: impl FnMut(F::Functor<(&Fix<F, Tag>, A)>) -> A) -> A
+        where F: Coherent<(), Tag>,
+              F::Functor<Fix<F, Tag>>: FunctorOnce<Fix<F, Tag>, Tag,
+                                                   Item = Fix<F, Tag>>,
+              F::Functor<usize>: Clone
+                  + FunctorOnce<usize, Tag, Item = usize>,
+    {
+        let mut fixes: Vec<Option<Fix<F, Tag>>> = vec![None];
+        let mut results: Vec<Option<A>> = vec![None];
+        let mut stack = vec![Task::Visit(self, 0)];
+        while let Some(task) = stack.pop() {
+            match task {
+                Task::Visit(node, slot) => {
+                    expand::<F, Tag, _, _>(
+                        node.unfix(), slot, &mut results, &mut stack);
+                    fixes.resize_with(results.len(), || None);
+                }
+                Task::Build(shape, slot) => {
+                    let fixes_ref = &fixes;
+                    let with = shape.clone().fmap_once(|i| (
+                        fixes_ref[i].as_ref().unwrap(),
+                        results[i].take().unwrap()));
+                    let a = alg(F::cohere::<usize, _>(with));
+                    let node = Fix::new(F::cohere::<usize, _>(
+                        shape.fmap_once(|i| fixes[i].take().unwrap())));
+                    fixes[slot] = Some(node);
+                    results[slot] = Some(a);
+                }
+            }
+        }
+        results[0].take().unwrap()
+    }
+}
+
+impl<F: TypeMap<(), Tag>, Tag> Drop for Fix<F, Tag> {
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        if let Some(layer) = self.layer.take() {
+            (self.children)(*layer, &mut stack);
+        }
+        while let Some(mut node) = stack.pop() {
+            if let Some(layer) = node.layer.take() {
+                (node.children)(*layer, &mut stack);
+            }
+        }
+    }
+}
+
+
+/// Work items for the explicit stack: expand a seed into a result slot, or
+/// build the result for a slot once its children are done.
+enum Task<S, L> {
+    Visit(S, usize),
+    Build(L, usize),
+}
+
+/// Replace each child of `layer` by a fresh result slot, and push the build
+/// of `slot` followed by visits to the children.
+fn expand<F, Tag, S, A>(layer: F::Functor<S>, slot: usize,
+                        results: &mut Vec<Option<A>>,
+                        stack: &mut Vec<Task<S, F::Functor<usize>>>)
+    where F: Coherent<(), Tag>,
+          F::Functor<S>: FunctorOnce<S, Tag, Item = S>,
+{
+    let mut children = Vec::new();
+    let shape = layer.fmap_once(|s| {
+        results.push(None);
+        children.push(Task::Visit(s, results.len() - 1));
+        results.len() - 1
+    });
+    stack.push(Task::Build(F::cohere::<S, usize>(shape), slot));
+    // Visit the children in order.
+    stack.extend(children.into_iter().rev());
+}
+
+/// Hylomorphism: unfold from `seed` with `coalg` and fold with `alg`, without
+/// building the intermediate structure.
+pub fn hylo<F, Tag, S, A>(seed: S, mut coalg: impl FnMut(S) -> F::Functor<S>,
+                          mut alg: impl FnMut(F::Functor<A>) -> A) -> A
+    where F: Coherent<(), Tag>,
+          F::Functor<S>: FunctorOnce<S, Tag, Item = S>,
+          F::Functor<usize>: FunctorOnce<usize, Tag, Item = usize>,
+{
+    let mut results: Vec<Option<A>> = vec![None];
+    let mut stack = vec![Task::Visit(seed, 0)];
+    while let Some(task) = stack.pop() {
+        match task {
+            Task::Visit(seed, slot) => expand::<F, Tag, _, _>(
+                coalg(seed), slot, &mut results, &mut stack),
+            Task::Build(shape, slot) => {
+                let layer = shape.fmap_once(|i| results[i].take().unwrap());
+                results[slot] = Some(alg(F::cohere::<usize, A>(layer)));
+            }
+        }
+    }
+    results[0].take().unwrap()
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum ExprF<R> { Lit(i64), Add(R, R), Neg(R) }
+    use ExprF::*;
+
+    impl<R> TypeMap<R> for ExprF<R> { type Functor<U> = ExprF<U>; }
+
+    impl<R> FunctorOnce<R> for ExprF<R> {
+        fn fmap_once<U>(self, mut f: impl FnMut(R) -> U) -> ExprF<U> {
+            match self {
+                Lit(x) => Lit(x),
+                Add(a, b) => { let a = f(a); Add(a, f(b)) }
+                Neg(a) => Neg(f(a)),
+            }
+        }
+    }
+
+    crate::coherent!(impl[R] Coherent<R> for ExprF<R> => ExprF<V>);
+
+    type Expr = Fix<ExprF<()>>;
+
+    fn eval(e: ExprF<i64>) -> i64 {
+        match e { Lit(x) => x, Add(a, b) => a + b, Neg(a) => -a }
+    }
+
+    #[test]
+    fn cata_ana() {
+        let e = Expr::new(Add(Expr::new(Lit(2)),
+                              Expr::new(Neg(Expr::new(Lit(5))))));
+        assert!(matches!(e.layer(), Add(_, _)));
+        assert_eq!(e.cata(eval), -3);
+
+        let show = Expr::ana((1, 3), |(lo, hi)| {
+            if lo == hi { Lit(lo) } else { Add((lo, lo), (lo + 1, hi)) }
+        }).cata(|e: ExprF<String>| match e {
+            Lit(x) => x.to_string(),
+            Add(a, b) => format!("({a}+{b})"),
+            Neg(a) => format!("-{a}"),
+        });
+        assert_eq!(show, "(1+(2+3))");
+    }
+
+    #[test]
+    fn para_test() {
+        // Count additions with a literal on the left.
+        let e = Expr::new(Add(Expr::new(Lit(1)),
+                              Expr::new(Add(Expr::new(Neg(Expr::new(Lit(2)))),
+                                            Expr::new(Lit(3))))));
+        let n = e.para(|e: ExprF<(&Expr, u32)>| match e {
+            Lit(_) => 0,
+            Add((l, a), (_, b)) => a + b + matches!(l.layer(), Lit(_)) as u32,
+            Neg((_, a)) => a,
+        });
+        assert_eq!(n, 1);
+    }
+
+    #[test]
+    fn deep() {
+        const DEPTH: i64 = 1_000_000;
+        let e = Expr::ana(DEPTH, |n| if n == 0 { Lit(1) } else { Neg(n - 1) });
+        assert_eq!(e.cata(eval), 1);
+        // Dropping without folding.
+        drop(Expr::ana(DEPTH, |n| if n == 0 { Lit(1) } else { Neg(n - 1) }));
+
+        let sum = hylo::<ExprF<()>, (), _, _>((1, DEPTH), |(lo, hi)| {
+            if lo == hi { return Lit(lo) }
+            let mid = (lo + hi) / 2;
+            Add((lo, mid), (mid + 1, hi))
+        }, eval);
+        assert_eq!(sum, DEPTH * (DEPTH + 1) / 2);
+    }
+}
@@ -4,6 +4,9 @@
 //! The RefMapable trait does the gory glue, it's implementations just need to
 //! specify the set of types to use.
 
+// `ref_app` borrows explicitly, to call the impls on references.
+#![cfg_attr(test, allow(clippy::needless_borrow))]
+
 use crate::{Applicative, Mapable, Mapped, RefIntoIterator};
 
 use std::collections::{LinkedList, VecDeque};
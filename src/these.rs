@@ -0,0 +1,218 @@
+//! Outer joins: `These<A, B>` is a value from either side or both, and `Align`
+//! pairs up two containers keeping the elements only on one side.
+
+use crate::{BiFunctor, BiFunctorMut, BiFunctorOnce, BiTypeMap, Mapable, Mapped,
+            TypeMap};
+use crate::bimapable::{BiMapable, Derived};
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{BuildHasher, Hash};
+
+use These::*;
+
+
+/// One value or the other, or both.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum These<A, B> {
+    This(A),
+    That(B),
+    Both(A, B),
+}
+
+impl<A, B> These<A, B> {
+    pub fn this(self) -> Option<A> {
+        match self { This(a) | Both(a, _) => Some(a), That(_) => None }
+    }
+
+    pub fn that(self) -> Option<B> {
+        match self { That(b) | Both(_, b) => Some(b), This(_) => None }
+    }
+
+    pub fn as_ref(&self) -> These<&A, &B> {
+        match self {
+            This(a) => This(a),
+            That(b) => That(b),
+            Both(a, b) => Both(a, b),
+        }
+    }
+
+    /// Build from a pair of options, `None` if both are.
+    pub fn from_options(a: Option<A>, b: Option<B>) -> Option<These<A, B>> {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(Both(a, b)),
+            (Some(a), None) => Some(This(a)),
+            (None, Some(b)) => Some(That(b)),
+            (None, None) => None,
+        }
+    }
+}
+
+impl<A> These<A, A> {
+    /// Collapse to a single value, combining with `f` if there are two.
+    pub fn merge(self, f: impl FnOnce(A, A) -> A) -> A {
+        match self { This(a) | That(a) => a, Both(a, b) => f(a, b) }
+    }
+}
+
+
+impl<A, B> BiTypeMap<A, B> for These<A, B> {
+    type BiFunctor<T, U> = These<T, U>;
+}
+
+impl<A, B> BiFunctorOnce<A, B> for These<A, B> {
+    fn fmap_once2<T, U>(self, mut f: impl FnMut(A) -> T,
+                        mut g: impl FnMut(B) -> U) -> These<T, U> {
+        match self {
+            This(a) => This(f(a)),
+            That(b) => That(g(b)),
+            Both(a, b) => Both(f(a), g(b)),
+        }
+    }
+}
+
+impl<A, B> BiFunctor<A, B> for These<A, B> {
+    fn fmap2<T, U>(&self, f: impl FnMut(&A) -> T, g: impl FnMut(&B) -> U)
+                   -> These<T, U> {
+        self.as_ref().fmap_once2(f, g)
+    }
+}
+
+impl<A, B> BiFunctorMut<A, B> for These<A, B> {
+    fn fmap_mut2<T, U>(&mut self, mut f: impl FnMut(&mut A) -> T,
+                       mut g: impl FnMut(&mut B) -> U) -> These<T, U> {
+        match self {
+            This(a) => This(f(a)),
+            That(b) => That(g(b)),
+            Both(a, b) => Both(f(a), g(b)),
+        }
+    }
+}
+
+crate::bicoherent!(impl[A, B] BiCoherent<A, B> for These<A, B> => These<U, V>);
+
+
+/// Functors that can be zipped, keeping elements with no partner.
+pub trait Align<T, Tag = ()> : TypeMap<T, Tag> {
+    /// Pair up the elements of `self` and `other`, and map with `f`.
+    fn align_with<U, V>(self, other: Self::Functor<U>,
+                        f: impl FnMut(These<T, U>) -> V) -> Self::Functor<V>;
+
+    fn align<U>(self, other: Self::Functor<U>) -> Self::Functor<These<T, U>>
+        where Self: Sized
+    {
+        self.align_with(other, |t| t)
+    }
+
+    /// Align, using `f` to combine elements present on both sides.
+    fn align_merge(self, other: Self, mut f: impl FnMut(T, T) -> T) -> Self
+        where Self: Sized + TypeMap<T, Tag, Functor<T> = Self>
+    {
+        self.align_with::<T, T>(other, |t| t.merge(&mut f))
+    }
+}
+
+/// Collections are aligned by position, so the result has the length of the
+/// longer.
+impl<T, C: Mapable<T>> Align<T, Mapped> for C {
+    fn align_with<U, V>(self, other: C::Collection<U>,
+                        mut f: impl FnMut(These<T, U>) -> V)
+                        -> C::Collection<V> {
+        let mut a = self.into_iter();
+        let mut b = other.into_iter();
+        std::iter::from_fn(|| These::from_options(a.next(), b.next()))
+            .map(&mut f).collect()
+    }
+}
+
+impl<T> Align<T> for Option<T> {
+    fn align_with<U, V>(self, other: Option<U>,
+                        f: impl FnMut(These<T, U>) -> V) -> Option<V> {
+        These::from_options(self, other).map(f)
+    }
+}
+
+/// Maps are aligned by key.
+impl<K: Eq + Hash, T, S: BuildHasher + Clone> Align<T, Derived<K, ()>>
+    for HashMap<K, T, S>
+{
+    fn align_with<U, V>(self, mut other: HashMap<K, U, S>,
+                        mut f: impl FnMut(These<T, U>) -> V)
+                        -> HashMap<K, V, S> {
+        let mut out = self.empty_like();
+        out.extend(self.into_iter().map(|(k, a)| {
+            let t = match other.remove(&k) {
+                Some(b) => Both(a, b),
+                None => This(a),
+            };
+            (k, f(t))
+        }));
+        out.extend(other.into_iter().map(|(k, b)| (k, f(That(b)))));
+        out
+    }
+}
+
+/// The values are visited in key order.
+impl<K: Ord, T> Align<T, Derived<K, ()>> for BTreeMap<K, T> {
+    fn align_with<U, V>(self, other: BTreeMap<K, U>,
+                        mut f: impl FnMut(These<T, U>) -> V)
+                        -> BTreeMap<K, V> {
+        let mut a = self.into_iter().peekable();
+        let mut b = other.into_iter().peekable();
+        std::iter::from_fn(|| {
+            let ord = match (a.peek(), b.peek()) {
+                (Some((k, _)), Some((l, _))) => k.cmp(l),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => return None,
+            };
+            Some(match ord {
+                Ordering::Less => {
+                    let (k, x) = a.next()?;
+                    (k, This(x))
+                }
+                Ordering::Greater => {
+                    let (k, y) = b.next()?;
+                    (k, That(y))
+                }
+                Ordering::Equal => {
+                    let ((k, x), (_, y)) = (a.next()?, b.next()?);
+                    (k, Both(x, y))
+                }
+            })
+        }).map(|(k, t)| (k, f(t))).collect()
+    }
+}
+
+
+#[test]
+fn these_test() {
+    let t: These<u8, &str> = Both(2, "ab");
+    assert_eq!(t.fmap2(|x| x * 2, |s| s.len()), Both(4, 2));
+    assert_eq!(That::<u8, _>("x").this(), None);
+    assert_eq!(This::<_, u8>(3).merge(|a, b| a + b), 3);
+    assert_eq!(Both(3, 4).merge(|a, b| a + b), 7);
+}
+
+#[test]
+fn align_test() {
+    let v = vec![1, 2, 3].align(vec!["a"]);
+    assert_eq!(v, [Both(1, "a"), This(2), This(3)]);
+    assert_eq!(vec![1].align_merge(vec![10, 20], |a, b| a + b), [11, 20]);
+    assert_eq!(None::<u8>.align(Some('c')), Some(That('c')));
+
+    let old = BTreeMap::from([("a", 1), ("b", 2)]);
+    let new = BTreeMap::from([("b", 3), ("c", 4)]);
+    let diff = old.clone().align_with(new.clone(), |t| match t {
+        This(_) => "removed",
+        That(_) => "added",
+        Both(x, y) => if x == y { "same" } else { "changed" },
+    });
+    assert_eq!(diff, BTreeMap::from(
+        [("a", "removed"), ("b", "changed"), ("c", "added")]));
+    assert_eq!(old.align_merge(new, |a, b| a * b),
+               BTreeMap::from([("a", 1), ("b", 6), ("c", 4)]));
+
+    let h = HashMap::from([(1, 'x')]).align(HashMap::from([(1, 1), (2, 2)]));
+    assert_eq!(h, HashMap::from([(1, Both('x', 1)), (2, That(2))]));
+}
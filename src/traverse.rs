@@ -0,0 +1,129 @@
+//! Traversing fixed size structures: arrays, and tuples of applicatives.
+//!
+//! The results are built up in place, in an array or tuple of `Option`s, with
+//! `lift2_once` filling in one element at a time.  So nothing is allocated on
+//! the heap, beyond what the applicative itself does.
+
+use crate::{ApplicativeOnce, Coherent, FunctorOnce, TypeMap};
+
+
+/// Turn a structure of applicatives into an applicative of the structure,
+/// e.g., `(Some(1), Some('a'))` into `Some((1, 'a'))`.
+pub trait Sequence<Items, Tag = ()> {
+    type Output;
+    fn sequence(self) -> Self::Output;
+}
+
+/// Map each element of an array into an applicative, and collect the results
+/// into an array.
+pub trait Traverse<T, const N: usize> {
+    fn traverse<U, Tag, F>(self, f: impl FnMut(T) -> F) -> F::Functor<[U; N]>
+        where U: Clone,
+              F: ApplicativeOnce<U, Tag> + Coherent<U, Tag>,
+              F::Functor<[Option<U>; N]>:
+                  ApplicativeOnce<[Option<U>; N], Tag, Functor<U> = F>
+                  + Coherent<[Option<U>; N], Tag>;
+}
+
+/// Combine the partial result `acc` with `x`, storing the value with `put`.
+fn fill<G, A, X, Tag>(acc: G, x: G::Functor<X>, put: impl Fn(&mut A, X)) -> G
+    where A: Clone, X: Clone,
+          G: ApplicativeOnce<A, Tag> + Coherent<A, Tag>,
+{
+    G::lift2_once(|mut a, x| { put(&mut a, x); a }, acc, x)
+}
+
+impl<T, const N: usize> Traverse<T, N> for [T; N] {
+    fn traverse<U, Tag, F>(self, mut f: impl FnMut(T) -> F)
+                           -> F::Functor<[U; N]>
+        where U: Clone,
+              F: ApplicativeOnce<U, Tag> + Coherent<U, Tag>,
+              F::Functor<[Option<U>; N]>:
+                  ApplicativeOnce<[Option<U>; N], Tag, Functor<U> = F>
+                  + Coherent<[Option<U>; N], Tag>
+    {
+        let mut acc = <F::Functor<[Option<U>; N]>>::pure_once(
+            std::array::from_fn(|_| None));
+        for (i, x) in self.into_iter().enumerate() {
+            acc = fill::<_, _, U, Tag>(
+                acc, f(x), move |a: &mut [Option<U>; N], u| a[i] = Some(u));
+        }
+        F::cohere::<[Option<U>; N], [U; N]>(
+            acc.fmap_once(|a| a.map(Option::unwrap)))
+    }
+}
+
+impl<U, Tag, F, const N: usize> Sequence<[U; N], Tag> for [F; N]
+    where U: Clone,
+          F: ApplicativeOnce<U, Tag> + Coherent<U, Tag>,
+          F::Functor<[Option<U>; N]>:
+              ApplicativeOnce<[Option<U>; N], Tag, Functor<U> = F>
+              + Coherent<[Option<U>; N], Tag>
+{
+    type Output = F::Functor<[U; N]>;
+    fn sequence(self) -> F::Functor<[U; N]> { self.traverse(|x| x) }
+}
+
+/// Tuples start from the first element, so need no `pure`.  The partial
+/// results are a tuple of `Option`s, `$acc`.
+macro_rules! sequence_tuple {
+    ($f0:ident $t0:ident $(, $f:ident $t:ident $i:tt)+) => {
+        sequence_tuple!(@impl (Option<$t0>, $(Option<$t>),+);
+                        $f0 $t0 $(, $f $t $i)+);
+    };
+    (@impl $acc:ty; $f0:ident $t0:ident $(, $f:ident $t:ident $i:tt)+) => {
+        impl<Tag, $f0, $t0: Clone, $($f, $t: Clone),+>
+            Sequence<($t0, $($t),+), Tag> for ($f0, $($f),+)
+            where
+            $f0: ApplicativeOnce<$t0, Tag> + Coherent<$t0, Tag>,
+            $f0::Functor<$acc>:
+                ApplicativeOnce<$acc, Tag> + Coherent<$acc, Tag>,
+            $($f0::Functor<$acc>: TypeMap<$acc, Tag, Functor<$t> = $f>,)+
+        {
+            type Output = $f0::Functor<($t0, $($t),+)>;
+
+            fn sequence(self) -> Self::Output {
+                let acc = self.0.fmap_once(|x| (Some(x), $(None::<$t>),+));
+                $(let acc = fill::<_, _, $t, Tag>(
+                    acc, self.$i, |a: &mut $acc, x| a.$i = Some(x));)+
+                $f0::cohere::<$acc, ($t0, $($t),+)>(
+                    acc.fmap_once(|a| (a.0.unwrap(), $(a.$i.unwrap()),+)))
+            }
+        }
+    };
+}
+
+sequence_tuple!(F0 T0, F1 T1 1);
+sequence_tuple!(F0 T0, F1 T1 1, F2 T2 2);
+sequence_tuple!(F0 T0, F1 T1 1, F2 T2 2, F3 T3 3);
+sequence_tuple!(F0 T0, F1 T1 1, F2 T2 2, F3 T3 3, F4 T4 4);
+sequence_tuple!(F0 T0, F1 T1 1, F2 T2 2, F3 T3 3, F4 T4 4, F5 T5 5);
+sequence_tuple!(F0 T0, F1 T1 1, F2 T2 2, F3 T3 3, F4 T4 4, F5 T5 5, F6 T6 6);
+sequence_tuple!(F0 T0, F1 T1 1, F2 T2 2, F3 T3 3, F4 T4 4, F5 T5 5, F6 T6 6,
+                F7 T7 7);
+
+
+#[test]
+fn traverse_array() {
+    let parse = |s: &str| s.parse::<u8>().ok();
+    assert_eq!(["1", "2", "3"].traverse(parse), Some([1, 2, 3]));
+    assert_eq!(["1", "x", "3"].traverse(parse), None);
+    assert_eq!([Some(1), Some(2)].sequence(), Some([1, 2]));
+    assert_eq!(<[Option<u8>; 0]>::sequence([]), Some([]));
+
+    let r: Result<[u8; 2], String> = [Ok(1), Err("e".to_string())].sequence();
+    assert_eq!(r, Err("e".into()));
+    let v = [vec![1, 2], vec![3, 4]].sequence();
+    assert_eq!(v, [[1, 3], [1, 4], [2, 3], [2, 4]]);
+}
+
+#[test]
+fn sequence_tuple() {
+    assert_eq!((Some(1), Some('a')).sequence(), Some((1, 'a')));
+    assert_eq!((Some(1), None::<char>, Some("x")).sequence(), None);
+    let r: Result<(u8, &str, bool), ()>
+        = (Ok(1), Ok("b"), Ok(true)).sequence();
+    assert_eq!(r, Ok((1, "b", true)));
+    let v = (vec![1, 2], vec!['a', 'b']).sequence();
+    assert_eq!(v, [(1, 'a'), (1, 'b'), (2, 'a'), (2, 'b')]);
+}
@@ -0,0 +1,339 @@
+//! Rose trees: a value with any number of subtrees.
+//!
+//! Everything here, including dropping, works with explicit stacks rather than
+//! recursion, so is safe on deep trees.  The functor maps and traversals visit
+//! the values in depth first pre-order.
+
+use crate::{Applicative, ApplicativeOnce, Apply, Functor, FunctorMut, FunctorMutate,
+            FunctorOnce, MonadOnce, TypeMap};
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+
+pub struct Tree<T> {
+    pub value: T,
+    pub children: Forest<T>,
+}
+
+/// The subtrees of a tree.  This is a `Vec` of trees, except that dropping it
+/// does not recurse.
+pub struct Forest<T>(pub Vec<Tree<T>>);
+
+impl<T> Tree<T> {
+    pub fn new(value: T, children: Vec<Tree<T>>) -> Tree<T> {
+        Tree { value, children: Forest(children) }
+    }
+
+    pub fn leaf(value: T) -> Tree<T> { Tree::new(value, Vec::new()) }
+
+    /// Depth first pre-order iteration.
+    pub fn dfs(&self) -> Dfs<'_, T> { Dfs(vec![self]) }
+
+    /// Breadth first iteration.
+    pub fn bfs(&self) -> Bfs<'_, T> { Bfs(VecDeque::from([self])) }
+
+    /// The number of values in the tree.
+    pub fn len(&self) -> usize { self.dfs().count() }
+
+    /// Always false, as a tree has at least one value.
+    pub fn is_empty(&self) -> bool { false }
+
+    /// The values in pre-order, each with its number of children.
+    fn flatten(self) -> Vec<(T, usize)> {
+        let mut out = Vec::new();
+        let mut stack = vec![self];
+        while let Some(Tree { value, mut children }) = stack.pop() {
+            out.push((value, children.len()));
+            stack.extend(std::mem::take(&mut children.0).into_iter().rev());
+        }
+        out
+    }
+
+    fn flatten_ref(&self) -> Vec<(&T, usize)> {
+        self.dfs_nodes().map(|t| (&t.value, t.children.len())).collect()
+    }
+
+    fn flatten_mut(&mut self) -> Vec<(&mut T, usize)> {
+        let mut out = Vec::new();
+        let mut stack = vec![self];
+        while let Some(Tree { value, children }) = stack.pop() {
+            out.push((value, children.len()));
+            stack.extend(children.iter_mut().rev());
+        }
+        out
+    }
+
+    fn dfs_nodes(&self) -> impl Iterator<Item = &Tree<T>> {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(node.children.iter().rev());
+            Some(node)
+        })
+    }
+}
+
+/// Rebuild a tree from pre-order items with their number of children, using
+/// `node` to combine an item with its already built subtrees.
+fn build<A, U>(flat: Vec<(A, usize)>,
+               mut node: impl FnMut(A, Vec<Tree<U>>) -> Tree<U>) -> Tree<U> {
+    // In reverse pre-order, subtrees are built before their parent, and the
+    // first child ends up on top of the stack.
+    let mut stack: Vec<Tree<U>> = Vec::new();
+    for (a, k) in flat.into_iter().rev() {
+        let children = stack.drain(stack.len() - k ..).rev().collect();
+        stack.push(node(a, children));
+    }
+    stack.pop().unwrap()
+}
+
+/// Monadic bind on a flattened tree: each item becomes a tree, with the bound
+/// subtrees appended to its children.
+fn bind_flat<A, U>(flat: Vec<(A, usize)>, mut f: impl FnMut(A) -> Tree<U>)
+                   -> Tree<U> {
+    let mapped = flat.into_iter().map(|(a, k)| (f(a), k)).collect();
+    build(mapped, |mut t, kids| { t.children.extend(kids); t })
+}
+
+impl<T> Deref for Forest<T> {
+    type Target = Vec<Tree<T>>;
+    fn deref(&self) -> &Vec<Tree<T>> { &self.0 }
+}
+
+impl<T> DerefMut for Forest<T> {
+    fn deref_mut(&mut self) -> &mut Vec<Tree<T>> { &mut self.0 }
+}
+
+impl<T> Drop for Forest<T> {
+    fn drop(&mut self) {
+        let mut stack = std::mem::take(&mut self.0);
+        while let Some(mut t) = stack.pop() {
+            stack.append(&mut t.children.0);
+        }
+    }
+}
+
+impl<T: Clone> Clone for Tree<T> {
+    fn clone(&self) -> Tree<T> { self.fmap(T::clone) }
+}
+
+impl<T: PartialEq> PartialEq for Tree<T> {
+    fn eq(&self, other: &Tree<T>) -> bool {
+        self.dfs_nodes().map(|t| (&t.value, t.children.len()))
+            .eq(other.dfs_nodes().map(|t| (&t.value, t.children.len())))
+    }
+}
+
+impl<T: Eq> Eq for Tree<T> {}
+
+/// A tree shows as its value followed by its children, e.g., `1 [2 [4], 3]`,
+/// with a leaf as just its value.
+impl<T: fmt::Debug> fmt::Debug for Tree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        debug_trees(f, std::slice::from_ref(self), false)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Forest<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        debug_trees(f, &self.0, true)
+    }
+}
+
+/// Write `trees` separated by commas, using an explicit stack of the trees and
+/// punctuation still to write.
+fn debug_trees<T: fmt::Debug>(f: &mut fmt::Formatter<'_>, trees: &[Tree<T>],
+                              brackets: bool) -> fmt::Result {
+    enum Step<'a, T> { Tree(&'a Tree<T>), Text(&'static str) }
+
+    fn push<'a, T>(stack: &mut Vec<Step<'a, T>>, trees: &'a [Tree<T>],
+                   brackets: bool) {
+        if brackets {
+            stack.push(Step::Text("]"));
+        }
+        for (i, t) in trees.iter().enumerate().rev() {
+            stack.push(Step::Tree(t));
+            if i > 0 {
+                stack.push(Step::Text(", "));
+            }
+        }
+        if brackets {
+            stack.push(Step::Text("["));
+        }
+    }
+
+    let mut stack = Vec::new();
+    push(&mut stack, trees, brackets);
+    while let Some(step) = stack.pop() {
+        match step {
+            Step::Text(s) => f.write_str(s)?,
+            Step::Tree(t) => {
+                t.value.fmt(f)?;
+                if !t.children.is_empty() {
+                    f.write_str(" ")?;
+                    push(&mut stack, &t.children, true);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+
+/// Depth first pre-order iterator.
+pub struct Dfs<'a, T>(Vec<&'a Tree<T>>);
+
+impl<'a, T> Iterator for Dfs<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.0.pop()?;
+        self.0.extend(node.children.iter().rev());
+        Some(&node.value)
+    }
+}
+
+/// Breadth first iterator.
+pub struct Bfs<'a, T>(VecDeque<&'a Tree<T>>);
+
+impl<'a, T> Iterator for Bfs<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.0.pop_front()?;
+        self.0.extend(node.children.iter());
+        Some(&node.value)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Tree<T> {
+    type Item = &'a T;
+    type IntoIter = Dfs<'a, T>;
+    fn into_iter(self) -> Dfs<'a, T> { self.dfs() }
+}
+
+
+impl<T> TypeMap<T> for Tree<T> { type Functor<U> = Tree<U>; }
+
+impl<T> FunctorOnce<T> for Tree<T> {
+    fn fmap_once<U>(self, mut f: impl FnMut(T) -> U) -> Tree<U> {
+        let mapped = self.flatten().into_iter().map(|(x, k)| (f(x), k));
+        build(mapped.collect(), Tree::new)
+    }
+}
+
+impl<'a, T: 'a> Functor<'a, T> for Tree<T> {
+    fn fmap<U>(&self, mut f: impl FnMut(&T) -> U) -> Tree<U> {
+        let mapped = self.flatten_ref().into_iter().map(|(x, k)| (f(x), k));
+        build(mapped.collect(), Tree::new)
+    }
+}
+
+impl<'a, T> FunctorMut<'a, T> for Tree<T> {
+    fn fmap_mut<U>(&mut self, mut f: impl FnMut(&mut T) -> U) -> Tree<U> {
+        let mapped = self.flatten_mut().into_iter().map(|(x, k)| (f(x), k));
+        build(mapped.collect(), Tree::new)
+    }
+}
+
+/// In place, with an explicit stack, so deep trees are fine.
+impl<T> FunctorMutate<T> for Tree<T> {
+    fn fmutate(&mut self, mut f: impl FnMut(&mut T)) {
+        let mut stack = vec![self];
+        while let Some(t) = stack.pop() {
+            f(&mut t.value);
+            stack.extend(t.children.iter_mut().rev());
+        }
+    }
+}
+
+crate::coherent!(impl[T] Coherent<T> for Tree<T> => Tree<V>);
+
+/// The applicative is that of the monad: each value of `a` gets a copy of `b`
+/// as extra children.
+impl<T> Apply<T> for Tree<T> {
+    fn lift2_once<U, V>(f: impl Fn(T, U) -> V, a: Tree<T>, b: Tree<U>)
+                        -> Tree<V>
+        where T: Clone, U: Clone
+    {
+        a.bind_once(|x| b.clone().fmap_once(|y| f(x.clone(), y)))
+    }
+
+    fn call_once<A, U>(self, x: Tree<A>) -> Tree<U>
+        where T: Fn(A) -> U, A: Clone
+    {
+        self.bind_once(|g| x.clone().fmap_once(g))
+    }
+}
+
+impl<T> ApplicativeOnce<T> for Tree<T> {
+    fn pure_once(x: T) -> Tree<T> { Tree::leaf(x) }
+}
+
+impl<'a, T: 'a> Applicative<'a, T> for Tree<T> {
+    fn pure(x: &T) -> Tree<T> where T: Clone { Tree::leaf(x.clone()) }
+
+    fn lift2<U: 'a, V: 'a>(f: impl Fn(&'a T, &'a U) -> V,
+                           a: &'a Tree<T>, b: &'a Tree<U>) -> Tree<V> {
+        let bs = b.flatten_ref();
+        bind_flat(a.flatten_ref(), |x| build(
+            bs.iter().map(|&(y, k)| (f(x, y), k)).collect(), Tree::new))
+    }
+}
+
+impl<T> MonadOnce<T> for Tree<T> {
+    fn bind_once<U>(self, f: impl FnMut(T) -> Tree<U>) -> Tree<U> {
+        bind_flat(self.flatten(), f)
+    }
+}
+
+
+#[test]
+fn tree_functor() {
+    let t = Tree::new(1, vec![Tree::new(2, vec![Tree::leaf(4)]),
+                              Tree::leaf(3)]);
+    assert_eq!(t.dfs().copied().collect::<Vec<_>>(), [1, 2, 4, 3]);
+    assert_eq!(t.bfs().copied().collect::<Vec<_>>(), [1, 2, 3, 4]);
+    assert_eq!(format!("{t:?}"), "1 [2 [4], 3]");
+    assert_eq!(format!("{:?}", t.children), "[2 [4], 3]");
+
+    let s = t.fmap(|x| x.to_string());
+    assert_eq!(s.children[0].children[0].value, "4");
+    let mut u = t.clone();
+    let old = u.fmap_mut(|x| { *x *= 10; *x / 10 });
+    assert_eq!(old, t);
+    assert_eq!(u.dfs().copied().collect::<Vec<_>>(), [10, 20, 40, 30]);
+    assert_eq!(t.fmap_once(|x| x + 1).bfs().copied().collect::<Vec<_>>(),
+               [2, 3, 4, 5]);
+    u.fmutate(|x| *x += 1);
+    assert_eq!(u.dfs().copied().collect::<Vec<_>>(), [11, 21, 41, 31]);
+}
+
+#[test]
+fn tree_monad() {
+    let t = Tree::new(1, vec![Tree::leaf(2)]);
+    let b = t.clone().bind_once(|x| Tree::new(x, vec![Tree::leaf(-x)]));
+    assert_eq!(b, Tree::new(1, vec![Tree::leaf(-1),
+                                    Tree::new(2, vec![Tree::leaf(-2)])]));
+
+    let u = Tree::new(10, vec![Tree::leaf(100)]);
+    let l = Tree::lift2_once(|x, y| x * y, t.clone(), u.clone());
+    let r = Tree::lift2(|x, y| x * y, &t, &u);
+    let expect = Tree::new(10, vec![
+        Tree::leaf(100), Tree::new(20, vec![Tree::leaf(200)])]);
+    assert_eq!(l, expect);
+    assert_eq!(r, expect);
+}
+
+#[test]
+fn tree_deep() {
+    let mut t = Tree::leaf(0u64);
+    for i in 1..1_000_000 {
+        t = Tree::new(i, vec![t]);
+    }
+    let u = t.fmap(|x| x + 1);
+    assert_eq!(u.dfs().last(), Some(&1));
+    assert_eq!(t.fmap_once(|x| x * 2).bfs().sum::<u64>(), 999_999 * 1_000_000);
+    assert_eq!(u.clone(), u);
+    assert!(format!("{u:?}").starts_with("1000000 [999999 [999998 ["));
+}